@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use glam::Vec3;
+use stardust_xr_fusion::{
+    audio::{Sound, SoundAspect},
+    spatial::{SpatialRefAspect, Transform},
+    values::ResourceID,
+};
+use tracing::debug;
+
+/// how long a played cue is kept around before its node is dropped, longer than any cue
+const CUE_LIFETIME: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    /// the ring attached to an input
+    Attach,
+    /// the ring detached from its input
+    Detach,
+    /// an object got captured
+    Capture,
+    /// an object was let go or thrown
+    Release,
+}
+impl Cue {
+    fn resource(&self) -> ResourceID {
+        let name = match self {
+            Cue::Attach => "attach",
+            Cue::Detach => "detach",
+            Cue::Capture => "capture",
+            Cue::Release => "release",
+        };
+        ResourceID::new_namespaced("absolute_solver", name)
+    }
+}
+
+/// plays [`Cue`]s, sounds that are still playing are kept alive until they're done.
+///
+/// the cues are loaded from `res/absolute_solver/{attach,detach,capture,release}.wav`, which
+/// don't ship with the solver. a missing file only means that cue stays silent
+#[derive(Default)]
+pub struct Sounds {
+    playing: Vec<(Sound, Instant)>,
+}
+impl Sounds {
+    /// plays `cue` at `position` relative to `parent`
+    pub fn play(&mut self, cue: Cue, parent: &impl SpatialRefAspect, position: Vec3) {
+        self.playing
+            .retain(|(_, started)| started.elapsed() < CUE_LIFETIME);
+        let sound = match Sound::create(
+            parent,
+            Transform::from_translation(position),
+            &cue.resource(),
+        ) {
+            Ok(sound) => sound,
+            Err(err) => {
+                debug!(?cue, ?err, "unable to create sound");
+                return;
+            }
+        };
+        if let Err(err) = sound.play() {
+            debug!(?cue, ?err, "unable to play sound");
+            return;
+        }
+        self.playing.push((sound, Instant::now()));
+    }
+}
@@ -0,0 +1,83 @@
+use stardust_xr_fusion::{
+    client::{Client, ClientState},
+    objects::object_registry::ObjectRegistry,
+    project_local_resources,
+    root::{RootAspect, RootEvent},
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{Instrument, debug, debug_span};
+
+use absolute_solver::{
+    config::Tuning,
+    ring::RingState,
+    session,
+    solver::{Solver, SolverConfig},
+};
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().init();
+    let client = Client::connect().await.unwrap();
+    client
+        .setup_resources(&[&project_local_resources!("res")])
+        .unwrap();
+    let event_loop = client.async_event_loop();
+    let client = event_loop.client_handle.clone();
+    let conn = session::connect().await.unwrap();
+    let (mut reconnects, _session_watch) = session::watch(conn.clone());
+    let obj_reg = ObjectRegistry::new(&conn).await;
+    let ring_state = client
+        .get_root()
+        .get_state()
+        .await
+        .ok()
+        .and_then(|state| state.data::<RingState>());
+    let mut config = SolverConfig::default();
+    if let Some(path) = Tuning::default_path() {
+        let tuning = Tuning::load(&path, config.tuning());
+        config.apply_tuning(&tuning);
+    }
+    config.selector.debug_scores = std::env::var_os("ABSOLUTE_SOLVER_DEBUG_SCORES").is_some();
+    let mut solver = Solver::new(client.clone(), conn, obj_reg, config, ring_state)
+        .await
+        .unwrap();
+    let mut events = solver.events();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => debug!(?event, "solver event"),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    loop {
+        event_loop.get_event_handle().wait().await;
+        let Some(event) = client.get_root().recv_root_event() else {
+            continue;
+        };
+        let frame_info = match event {
+            RootEvent::Ping { response } => {
+                response.send_ok(());
+                continue;
+            }
+            RootEvent::Frame { info } => info,
+            RootEvent::SaveState { response } => {
+                response.wrap(|| {
+                    ClientState::from_data_root(Some(solver.ring.state()), client.get_root())
+                });
+                break;
+            }
+        };
+        while let Ok(conn) = reconnects.try_recv() {
+            let obj_reg = ObjectRegistry::new(&conn).await;
+            solver.reconnect(conn, obj_reg).await;
+        }
+        solver
+            .frame(&frame_info)
+            .instrument(debug_span!("frame", elapsed = frame_info.elapsed))
+            .await;
+    }
+    solver.shutdown().await;
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tracing::warn;
+
+use crate::{
+    mover::{DEFAULT_RATE, MoverMotion},
+    solver::SolverConfig,
+};
+
+/// the parts of [`SolverConfig`] that can be tuned from a config file, see [`Tuning::load`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    /// see [`RingSettings::radius`](crate::ring::RingSettings::radius)
+    pub ring_radius: f32,
+    /// see [`RingSettings::thickness`](crate::ring::RingSettings::thickness)
+    pub ring_thickness: f32,
+    /// see [`SpreadThresholds`](crate::solver::SpreadThresholds)
+    pub spread_enter: f32,
+    pub spread_exit: f32,
+    /// see [`SelectorSettings::selection_cone`](crate::selection::SelectorSettings::selection_cone)
+    pub selection_cone: f32,
+    /// see [`SelectorSettings::max_range`](crate::selection::SelectorSettings::max_range)
+    pub max_range: f32,
+    /// see [`MoverMotion::Lerp`]
+    pub position_responsiveness: f32,
+    pub rotation_responsiveness: f32,
+}
+impl Tuning {
+    /// `$XDG_CONFIG_HOME/absolute_solver/config.toml`, or in `~/.config` if that isn't set
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("absolute_solver").join("config.toml"))
+    }
+    /// reads the file at `path` on top of `defaults`.
+    ///
+    /// fields missing from the file keep their default, malformed fields are logged and keep
+    /// their default too, so a typo doesn't throw away the rest of the file.
+    /// a missing or unparsable file keeps every default
+    pub fn load(path: &Path, defaults: Tuning) -> Tuning {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return defaults,
+            Err(err) => {
+                warn!(%err, ?path, "unable to read config, using the defaults");
+                return defaults;
+            }
+        };
+        let table = match toml::from_str::<toml::Table>(&text) {
+            Ok(table) => table,
+            Err(err) => {
+                warn!(%err, ?path, "unable to parse config, using the defaults");
+                return defaults;
+            }
+        };
+        let mut tuning = defaults;
+        load_field(&table, "ring_radius", &mut tuning.ring_radius);
+        load_field(&table, "ring_thickness", &mut tuning.ring_thickness);
+        load_field(&table, "spread_enter", &mut tuning.spread_enter);
+        load_field(&table, "spread_exit", &mut tuning.spread_exit);
+        load_field(&table, "selection_cone", &mut tuning.selection_cone);
+        load_field(&table, "max_range", &mut tuning.max_range);
+        load_field(
+            &table,
+            "position_responsiveness",
+            &mut tuning.position_responsiveness,
+        );
+        load_field(
+            &table,
+            "rotation_responsiveness",
+            &mut tuning.rotation_responsiveness,
+        );
+        tuning
+    }
+    /// writes the tuning to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            toml::to_string_pretty(self).map_err(io::Error::other)?,
+        )
+    }
+}
+
+fn load_field<T: DeserializeOwned>(table: &toml::Table, key: &str, field: &mut T) {
+    let Some(value) = table.get(key) else {
+        return;
+    };
+    match value.clone().try_into() {
+        Ok(value) => *field = value,
+        Err(err) => warn!(key, %err, "malformed config field, using the default"),
+    }
+}
+
+impl SolverConfig {
+    pub fn tuning(&self) -> Tuning {
+        let (position_responsiveness, rotation_responsiveness) = match self.solver.motion {
+            MoverMotion::Lerp { position, rotation } => (position, rotation),
+            MoverMotion::Spring { .. } => (DEFAULT_RATE, DEFAULT_RATE),
+        };
+        Tuning {
+            ring_radius: self.ring.radius,
+            ring_thickness: self.ring.thickness,
+            spread_enter: self.solver.spread.enter,
+            spread_exit: self.solver.spread.exit,
+            selection_cone: self.selector.selection_cone,
+            max_range: self.selector.max_range,
+            position_responsiveness,
+            rotation_responsiveness,
+        }
+    }
+    /// the responsivenesses only apply while [`SolverSettings::motion`](crate::solver::SolverSettings::motion)
+    /// is [`MoverMotion::Lerp`]
+    pub fn apply_tuning(&mut self, tuning: &Tuning) {
+        self.ring.radius = tuning.ring_radius;
+        self.ring.thickness = tuning.ring_thickness;
+        self.solver.spread.enter = tuning.spread_enter;
+        self.solver.spread.exit = tuning.spread_exit;
+        self.selector.selection_cone = tuning.selection_cone.max(f32::EPSILON);
+        self.selector.max_range = tuning.max_range;
+        if let MoverMotion::Lerp { .. } = self.solver.motion {
+            self.solver.motion = MoverMotion::Lerp {
+                position: tuning.position_responsiveness,
+                rotation: tuning.rotation_responsiveness,
+            };
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::f32::consts::TAU;
+
+use glam::Vec3;
+
+/// see [`OneEuroFilter`]
+#[derive(Debug, Clone, Copy)]
+pub struct OneEuroSettings {
+    /// cutoff frequency (Hz) while the input is still, lower smooths out more jitter
+    pub min_cutoff: f32,
+    /// how much the cutoff rises with speed (Hz per m/s), higher lags less behind fast motion
+    pub beta: f32,
+    /// cutoff frequency (Hz) for the speed estimate the cutoff adapts to
+    pub derivative_cutoff: f32,
+}
+impl Default for OneEuroSettings {
+    fn default() -> Self {
+        Self {
+            min_cutoff: 2.0,
+            beta: 20.0,
+            derivative_cutoff: 1.0,
+        }
+    }
+}
+
+/// low-pass filter that smooths a lot while its input barely moves and hardly at all while it
+/// moves fast, so jitter is removed without lagging behind deliberate motion.
+///
+/// see <https://gery.casiez.net/1euro/>
+#[derive(Debug, Clone, Default)]
+pub struct OneEuroFilter {
+    // filtered value and speed of the last sample
+    last: Option<(Vec3, Vec3)>,
+}
+impl OneEuroFilter {
+    /// filters `value` sampled `delta` seconds after the previous one
+    pub fn filter(&mut self, value: Vec3, delta: f32, settings: &OneEuroSettings) -> Vec3 {
+        let Some((last_value, last_speed)) = self.last.filter(|_| delta > 0.0) else {
+            self.last = Some((value, Vec3::ZERO));
+            return value;
+        };
+        let speed = last_speed.lerp(
+            (value - last_value) / delta,
+            smoothing_factor(settings.derivative_cutoff, delta),
+        );
+        let cutoff = settings.min_cutoff + settings.beta * speed.length();
+        let value = last_value.lerp(value, smoothing_factor(cutoff, delta));
+        self.last = Some((value, speed));
+        value
+    }
+    /// forgets the previous samples, the next one passes through unfiltered
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+    /// whether `value` is further than `threshold` from the last filtered value,
+    /// see [`is_jump`]
+    pub fn jumps_to(&self, value: Vec3, threshold: Option<f32>) -> bool {
+        is_jump(self.last.map(|(last, _)| last), value, threshold)
+    }
+}
+
+/// whether moving from `last` to `value` is a teleport rather than motion,
+/// never with no `last` value or no `threshold`
+pub fn is_jump(last: Option<Vec3>, value: Vec3, threshold: Option<f32>) -> bool {
+    threshold.is_some_and(|threshold| last.is_some_and(|last| last.distance(value) > threshold))
+}
+
+fn smoothing_factor(cutoff: f32, delta: f32) -> f32 {
+    let time_constant = 1.0 / (TAU * cutoff.max(f32::EPSILON));
+    1.0 / (1.0 + time_constant / delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converges_right_away_after_a_jump() {
+        let settings = OneEuroSettings::default();
+        let delta = 1.0 / 90.0;
+        let mut filter = OneEuroFilter::default();
+        for _ in 0..90 {
+            filter.filter(Vec3::ZERO, delta, &settings);
+        }
+        let teleported = Vec3::new(2.0, 0.0, 0.0);
+        assert!(filter.jumps_to(teleported, Some(0.5)));
+        assert!(!filter.jumps_to(teleported, None));
+        // without a reset the filter glides over
+        let mut gliding = filter.clone();
+        assert!(
+            gliding
+                .filter(teleported, delta, &settings)
+                .distance(teleported)
+                > 0.01
+        );
+
+        filter.reset();
+        assert_eq!(filter.filter(teleported, delta, &settings), teleported);
+        for _ in 0..10 {
+            let filtered = filter.filter(teleported, delta, &settings);
+            assert!(filtered.distance(teleported) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn small_steps_are_no_jump() {
+        assert!(!is_jump(None, Vec3::splat(10.0), Some(0.5)));
+        assert!(!is_jump(
+            Some(Vec3::ZERO),
+            Vec3::new(0.4, 0.0, 0.0),
+            Some(0.5)
+        ));
+        assert!(is_jump(
+            Some(Vec3::ZERO),
+            Vec3::new(0.6, 0.0, 0.0),
+            Some(0.5)
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use stardust_xr_fusion::input::InputData;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::selection::SolverEvent;
+
+/// a single vibration of an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pulse {
+    /// how strong the vibration is, `0.0..=1.0`
+    pub intensity: f32,
+    pub duration: Duration,
+}
+
+/// which pulse each solver event plays on the input the ring is attached to,
+/// `None` to not pulse on that event
+#[derive(Debug, Clone, Copy)]
+pub struct HapticSettings {
+    /// something got highlighted
+    pub hover: Option<Pulse>,
+    /// an object got captured
+    pub capture: Option<Pulse>,
+    /// an object was let go or thrown
+    pub release: Option<Pulse>,
+}
+impl Default for HapticSettings {
+    fn default() -> Self {
+        Self {
+            hover: Some(Pulse {
+                intensity: 0.1,
+                duration: Duration::from_millis(10),
+            }),
+            capture: Some(Pulse {
+                intensity: 0.6,
+                duration: Duration::from_millis(40),
+            }),
+            release: Some(Pulse {
+                intensity: 0.3,
+                duration: Duration::from_millis(20),
+            }),
+        }
+    }
+}
+impl HapticSettings {
+    /// the pulse `event` plays, if any
+    pub fn pulse_for(&self, event: &SolverEvent) -> Option<Pulse> {
+        match event {
+            SolverEvent::HoverChanged(Some(_)) => self.hover,
+            SolverEvent::HoverChanged(None) => None,
+            SolverEvent::Captured(_) => self.capture,
+            SolverEvent::Released(_) => self.release,
+        }
+    }
+}
+
+/// something that can vibrate inputs, e.g. controllers through the runtime they come from
+pub trait HapticOutput: Send {
+    /// whether `input` can vibrate at all, pulses for inputs that can't are skipped
+    fn supports(&self, input: &InputData) -> bool;
+    fn pulse(&mut self, input: &InputData, pulse: Pulse);
+}
+
+/// input methods don't expose haptic output yet, so this is the default and supports no input
+#[derive(Debug, Default)]
+pub struct NoHaptics;
+impl HapticOutput for NoHaptics {
+    fn supports(&self, _input: &InputData) -> bool {
+        false
+    }
+    fn pulse(&mut self, _input: &InputData, _pulse: Pulse) {}
+}
+
+/// plays the pulses of [`HapticSettings`] for solver events on a [`HapticOutput`]
+pub struct Haptics {
+    settings: HapticSettings,
+    output: Box<dyn HapticOutput>,
+    events: broadcast::Receiver<SolverEvent>,
+}
+impl Haptics {
+    pub fn new(settings: HapticSettings, events: broadcast::Receiver<SolverEvent>) -> Self {
+        Self {
+            settings,
+            output: Box::new(NoHaptics),
+            events,
+        }
+    }
+    pub fn set_output(&mut self, output: Box<dyn HapticOutput>) {
+        self.output = output;
+    }
+    /// pulses `input` for every event since the last update,
+    /// events while there's no input or one that can't vibrate are dropped
+    pub fn update(&mut self, input: Option<&InputData>) {
+        loop {
+            let event = match self.events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            };
+            let Some(pulse) = self.settings.pulse_for(&event) else {
+                continue;
+            };
+            if let Some(input) = input.filter(|input| self.output.supports(input)) {
+                self.output.pulse(input, pulse);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selection::ObjectId;
+
+    #[test]
+    fn capture_pulses_stronger_than_hover() {
+        let settings = HapticSettings::default();
+        let id = ObjectId {
+            destination: "org.example".to_string(),
+            path: "/object".to_string(),
+        };
+        let hover = settings
+            .pulse_for(&SolverEvent::HoverChanged(Some(id.clone())))
+            .unwrap();
+        let capture = settings
+            .pulse_for(&SolverEvent::Captured(id.clone()))
+            .unwrap();
+        assert!(capture.intensity > hover.intensity);
+        assert!(
+            settings
+                .pulse_for(&SolverEvent::HoverChanged(None))
+                .is_none()
+        );
+        let silent = HapticSettings {
+            release: None,
+            ..settings
+        };
+        assert!(silent.pulse_for(&SolverEvent::Released(id)).is_none());
+    }
+
+    #[test]
+    fn events_without_input_are_dropped() {
+        let (events, receiver) = broadcast::channel(4);
+        let mut haptics = Haptics::new(HapticSettings::default(), receiver);
+        events.send(SolverEvent::HoverChanged(None)).unwrap();
+        haptics.update(None);
+        assert!(matches!(
+            haptics.events.try_recv(),
+            Err(TryRecvError::Empty)
+        ));
+    }
+}
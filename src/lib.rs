@@ -0,0 +1,11 @@
+pub mod audio;
+pub mod config;
+pub mod filter;
+pub mod haptics;
+pub mod mover;
+pub mod recording;
+pub mod ring;
+pub mod scale_handles;
+pub mod selection;
+pub mod session;
+pub mod solver;
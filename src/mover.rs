@@ -1,28 +1,328 @@
-use glam::{FloatExt, Quat, Vec3, Vec3A};
+use std::time::Instant;
+
+use glam::{EulerRot, FloatExt, Mat4, Quat, Vec3, Vec3A};
 use stardust_xr_fusion::{
-    node::NodeResult,
+    fields::{FieldRef, FieldRefAspect},
+    node::{NodeResult, NodeType},
     spatial::{Spatial, SpatialAspect, SpatialRef, SpatialRefAspect, Transform},
 };
 
-use crate::selection::CapturedSelection;
+use tracing::warn;
+
+use crate::{
+    filter::is_jump,
+    selection::{CapturedSelection, snap_to_grid, trace_surface},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationMode {
+    /// the selection takes on the rotation of the input
+    #[default]
+    Follow,
+    /// the selection keeps the rotation it had relative to the input when it was captured and
+    /// only turns as much as the input turns after that, so it can be turned bit by bit
+    Relative,
+    /// the selection keeps the world rotation it had when it was captured,
+    /// even while the input moves and rotates
+    Fixed,
+}
+
+/// limits how a held selection can move, axes are relative to the input
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MoveConstraint {
+    #[default]
+    FreeMove,
+    /// only slide along this axis
+    AxisLocked(Vec3),
+    /// only slide within the plane with this normal
+    PlaneLocked(Vec3),
+    /// move freely but only rotate around this axis
+    RotationLockedTo(Vec3),
+}
+impl MoveConstraint {
+    /// limits the move from `from` to `to` to the allowed directions
+    fn constrain_translation(&self, from: Vec3, to: Vec3) -> Vec3 {
+        let delta = to - from;
+        match self {
+            MoveConstraint::AxisLocked(axis) => from + delta.project_onto(*axis),
+            MoveConstraint::PlaneLocked(normal) => from + delta.reject_from(*normal),
+            MoveConstraint::FreeMove | MoveConstraint::RotationLockedTo(_) => to,
+        }
+    }
+    /// limits the rotation from `from` to `to` to the allowed axis
+    fn constrain_rotation(&self, from: Quat, to: Quat) -> Quat {
+        let MoveConstraint::RotationLockedTo(axis) = self else {
+            return to;
+        };
+        // the twist part of a swing-twist decomposition
+        let delta = to * from.inverse();
+        let twist_axis = Vec3::new(delta.x, delta.y, delta.z).project_onto(*axis);
+        let twist = Quat::from_xyzw(twist_axis.x, twist_axis.y, twist_axis.z, delta.w);
+        if twist.length_squared() <= f32::EPSILON {
+            return from;
+        }
+        twist.normalize() * from
+    }
+}
+
+/// world aligned snapping for held objects, while they're held or once they're let go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapSettings {
+    /// size of the grid positions snap to (m), `0.0` to not snap the position
+    pub translation_step: f32,
+    /// rotations snap to multiples of this around each axis, `0.0` to not snap the rotation
+    pub rotation_step_radians: f32,
+    /// only snap the rotation around +Y (yaw), keeping pitch and roll as they are
+    pub yaw_only: bool,
+    /// move freely while held and only snap once let go, continuous snapping can feel sticky
+    pub on_release_only: bool,
+}
+impl SnapSettings {
+    /// snaps a pose relative to the client root
+    pub fn snap(&self, translation: Vec3, rotation: Quat) -> (Vec3, Quat) {
+        let translation = if self.translation_step > 0.0 {
+            snap_to_grid(translation, self.translation_step)
+        } else {
+            translation
+        };
+        let rotation = if self.rotation_step_radians > 0.0 {
+            let step = self.rotation_step_radians;
+            let (y, x, z) = rotation.to_euler(EulerRot::YXZ);
+            let round = |angle: f32| (angle / step).round() * step;
+            if self.yaw_only {
+                Quat::from_euler(EulerRot::YXZ, round(y), x, z)
+            } else {
+                Quat::from_euler(EulerRot::YXZ, round(y), round(x), round(z))
+            }
+        } else {
+            rotation
+        };
+        (translation, rotation)
+    }
+}
+
+/// the rate used when an object doesn't provide a mass hint
+pub const DEFAULT_RATE: f32 = 0.95;
+/// the refresh rate (Hz) rates are tuned for, at other rates the smoothing is scaled to feel the same
+const REFERENCE_RATE: f32 = 90.0;
+/// frame deltas (s) are clamped to this range so a zero delta doesn't freeze the selection
+/// and a stall doesn't teleport it
+const MIN_DELTA: f32 = 1.0 / 240.0;
+const MAX_DELTA: f32 = 0.1;
+/// how close (m) [`Mover::adjust_distance`] can reel the selection in
+pub const MIN_DISTANCE: f32 = 0.1;
+/// how far (m) [`Mover::adjust_distance`] can push the selection out
+pub const MAX_DISTANCE: f32 = 5.0;
+
+/// maps an object's mass hint (kg) to a lerp rate, see [`MoverMotion::with_mass`],
+/// light objects snap to the input while heavy ones lag behind (approaching 1.0)
+pub fn rate_for_mass(mass: f32) -> f32 {
+    (1.0 - (0.5 / (1.0 + mass.max(0.0)))).clamp(0.5, 0.99)
+}
+
+/// how the selection catches up with the input, see [`Mover::set_motion`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoverMotion {
+    /// keep `position` of the selection's current position and `rotation` of its current rotation
+    /// each update at 90Hz (0..=1), higher values make the selection follow the input more
+    /// sluggishly. a gentler rotation than position keeps placement precise without the
+    /// selection swinging around uncomfortably
+    Lerp { position: f32, rotation: f32 },
+    /// pull the selection towards the input like a damped spring, snappier on quick flicks.
+    /// `damping` of `2.0 * stiffness.sqrt()` settles fastest without overshooting,
+    /// see [`MoverMotion::critically_damped`]
+    Spring { stiffness: f32, damping: f32 },
+}
+impl MoverMotion {
+    pub fn critically_damped(stiffness: f32) -> Self {
+        Self::Spring {
+            stiffness,
+            damping: 2.0 * stiffness.max(0.0).sqrt(),
+        }
+    }
+    /// the motion made lighter or heavier by an object's mass hint (kg), an object whose
+    /// [`rate_for_mass`] is [`DEFAULT_RATE`] moves like the motion itself.
+    /// lerp rates follow the input that much faster or slower, springs get stiffer or softer
+    /// while keeping their damping ratio
+    pub fn with_mass(self, mass: f32) -> Self {
+        let follow = (1.0 - rate_for_mass(mass)) / (1.0 - DEFAULT_RATE);
+        let scale_rate = |rate: f32| (1.0 - ((1.0 - rate) * follow)).clamp(0.0, 1.0);
+        match self {
+            Self::Lerp { position, rotation } => Self::Lerp {
+                position: scale_rate(position),
+                rotation: scale_rate(rotation),
+            },
+            Self::Spring { stiffness, damping } => Self::Spring {
+                stiffness: stiffness * follow,
+                damping: damping * follow.sqrt(),
+            },
+        }
+    }
+}
+impl Default for MoverMotion {
+    fn default() -> Self {
+        Self::Lerp {
+            position: DEFAULT_RATE,
+            rotation: DEFAULT_RATE,
+        }
+    }
+}
+
+/// longest time step (s) springs are integrated with, longer frames are split up so stiff
+/// springs don't blow up
+const SPRING_STEP: f32 = 1.0 / 240.0;
+
+/// velocities a [`MoverMotion::Spring`] carries between updates
+#[derive(Debug, Clone, Copy, Default)]
+struct SpringState {
+    // m/s
+    distance: f32,
+    // rotation axis scaled by radians/s, of the direction from the input to the selection
+    direction: Vec3,
+    // rotation axis scaled by radians/s, of the selection itself
+    rotation: Vec3,
+}
+
+/// moves `value` towards `target` like a damped spring for `delta` seconds
+fn spring_f32(
+    value: f32,
+    target: f32,
+    velocity: &mut f32,
+    (stiffness, damping): (f32, f32),
+    delta: f32,
+) -> f32 {
+    let steps = (delta / SPRING_STEP).ceil().max(1.0);
+    let dt = delta / steps;
+    let mut value = value;
+    for _ in 0..steps as u32 {
+        *velocity += ((stiffness * (target - value)) - (damping * *velocity)) * dt;
+        value += *velocity * dt;
+    }
+    value
+}
+
+/// rotational version of [`spring_f32`]
+fn spring_quat(
+    value: Quat,
+    target: Quat,
+    velocity: &mut Vec3,
+    (stiffness, damping): (f32, f32),
+    delta: f32,
+) -> Quat {
+    let steps = (delta / SPRING_STEP).ceil().max(1.0);
+    let dt = delta / steps;
+    let mut value = value;
+    for _ in 0..steps as u32 {
+        let mut error = target * value.inverse();
+        // take the short way around
+        if error.w < 0.0 {
+            error = -error;
+        }
+        *velocity += ((stiffness * error.to_scaled_axis()) - (damping * *velocity)) * dt;
+        value = (Quat::from_scaled_axis(*velocity * dt) * value).normalize();
+    }
+    value
+}
+
+/// throws slower than this (m/s) aren't worth continuing
+const MIN_THROW_SPEED: f32 = 0.05;
+/// rad/s, spins faster than this are almost certainly tracking jitter
+const MAX_THROW_SPIN: f32 = 20.0;
+
+/// a box held objects can't be moved out of
+#[derive(Debug, Clone)]
+pub struct MoveBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+    /// the space `min` and `max` are relative to, `None` for the client root
+    pub space: Option<SpatialRef>,
+}
+
+/// velocity of something sampled once per update, see [`Mover::velocity`]
+#[derive(Debug, Clone, Copy, Default)]
+struct VelocityTracker {
+    // m/s
+    velocity: Vec3A,
+    // rotation axis scaled by radians/s
+    angular_velocity: Vec3A,
+    // position and rotation of the last sample and when it was taken
+    last_pose: Option<(Vec3A, Quat, Instant)>,
+}
+impl VelocityTracker {
+    fn sample(&mut self, pos: Vec3A, rot: Quat, now: Instant) {
+        if let Some((last_pos, last_rot, last_time)) = self.last_pose {
+            let delta = now.duration_since(last_time).as_secs_f32();
+            if delta > 0.0 {
+                let velocity = (pos - last_pos) / delta;
+                let angular_velocity =
+                    Vec3A::from((rot * last_rot.inverse()).to_scaled_axis()) / delta;
+                // smooth out tracking jitter
+                self.velocity = self.velocity.lerp(velocity, 0.5);
+                self.angular_velocity = self.angular_velocity.lerp(angular_velocity, 0.5);
+            }
+        }
+        self.last_pose = Some((pos, rot, now));
+    }
+    /// the tracker a mover taking over the selection continues with, see [`Mover::hand_off`].
+    /// keeps the velocity and the last sample, so the next sample continues from them
+    /// instead of starting from rest
+    fn handed_off(&self) -> Self {
+        Self {
+            velocity: self.velocity,
+            angular_velocity: self.angular_velocity,
+            last_pose: self.last_pose,
+        }
+    }
+}
 
 pub struct Mover {
     selection: CapturedSelection,
     target: Spatial,
     input: SpatialRef,
-    // m/s
-    // selection_velocity: Vec3A,
-    // rotation axis scaled by radians/s
-    // selection_angular_velocity: Vec3A,
+    rotation_mode: RotationMode,
+    constraint: MoveConstraint,
+    // rotation of the selection relative to the client root when it was captured
+    capture_rotation: Quat,
+    // rotation of the selection relative to the input when it was captured
+    grab_rotation: Quat,
+    // of the selection relative to the client root
+    velocity: VelocityTracker,
+    // distance between the input and the selection
+    distance: f32,
+    // distance between the input and the target
+    target_distance: f32,
+    motion: MoverMotion,
+    spring: SpringState,
+    // the user's head and how far the selection has to stay away from it
+    head: Option<(SpatialRef, f32)>,
+    // when the selection jumps further than this relative to the input between updates,
+    // smoothing is skipped for that update
+    jump_threshold: Option<f32>,
+    // translation relative to the input set in the last update
+    last_translation: Option<Vec3>,
+    scale: Vec3,
+    bounds: Option<MoveBounds>,
+    snap: Option<SnapSettings>,
+    // fields the selection can't pass through
+    obstacles: Vec<FieldRef>,
 }
 
 impl Mover {
     pub async fn new(selection: CapturedSelection, input_spatial: SpatialRef) -> NodeResult<Self> {
         let target = Spatial::create(&input_spatial, Transform::none())?;
-        let len = selection
+        let capture_rotation = selection
             .spatial()
-            .get_transform(&input_spatial)
+            .get_transform(selection.spatial().client().get_root())
             .await?
+            .rotation
+            .map(Quat::from)
+            .unwrap_or_default();
+        let relative_transform = selection.spatial().get_transform(&input_spatial).await?;
+        let grab_rotation = relative_transform
+            .rotation
+            .map(Quat::from)
+            .unwrap_or_default();
+        let len = relative_transform
             .translation
             .map(Vec3A::from)
             .unwrap_or_default()
@@ -32,12 +332,277 @@ impl Mover {
             selection,
             target,
             input: input_spatial,
+            rotation_mode: RotationMode::default(),
+            constraint: MoveConstraint::default(),
+            capture_rotation,
+            grab_rotation,
+            velocity: VelocityTracker::default(),
+            distance: len,
+            target_distance: len,
+            motion: MoverMotion::default(),
+            spring: SpringState::default(),
+            head: None,
+            jump_threshold: None,
+            last_translation: None,
+            scale: Vec3::ONE,
+            bounds: None,
+            snap: None,
+            obstacles: Vec::new(),
         })
     }
-    pub async fn update(&mut self) {
+    /// moves the selection over to another input (e.g. the other hand) without releasing it.
+    ///
+    /// the tracked velocity and all settings carry over so the selection doesn't stop dead,
+    /// a handoff is meant to happen once both hands held the selection and the first one lets go
+    pub async fn hand_off(self, input_spatial: SpatialRef) -> NodeResult<Self> {
+        let Mover {
+            selection,
+            rotation_mode,
+            constraint,
+            capture_rotation,
+            velocity,
+            motion,
+            spring,
+            head,
+            jump_threshold,
+            scale,
+            bounds,
+            snap,
+            ..
+        } = self;
+        let mut mover = Mover::new(selection, input_spatial).await?;
+        mover.rotation_mode = rotation_mode;
+        mover.constraint = constraint;
+        mover.capture_rotation = capture_rotation;
+        mover.velocity = velocity.handed_off();
+        mover.motion = motion;
+        mover.spring = spring;
+        mover.head = head;
+        mover.jump_threshold = jump_threshold;
+        mover.scale = scale;
+        mover.bounds = bounds;
+        mover.snap = snap;
+        Ok(mover)
+    }
+    pub fn set_rotation_mode(&mut self, rotation_mode: RotationMode) {
+        self.rotation_mode = rotation_mode;
+    }
+    pub fn set_constraint(&mut self, constraint: MoveConstraint) {
+        self.constraint = constraint;
+    }
+    pub fn set_motion(&mut self, motion: MoverMotion) {
+        self.motion = match motion {
+            MoverMotion::Lerp { position, rotation } => MoverMotion::Lerp {
+                position: position.clamp(0.0, 1.0),
+                rotation: rotation.clamp(0.0, 1.0),
+            },
+            spring => spring,
+        };
+        self.spring = SpringState::default();
+    }
+    /// keeps the selection at least `clearance` meters away from `head`, `None` to disable
+    pub fn set_head(&mut self, head: Option<SpatialRef>, clearance: f32) {
+        self.head = head.map(|head| (head, clearance));
+    }
+    /// snap to the target instead of smoothing when the input teleports further than
+    /// `threshold` meters in a single update, `None` to always smooth
+    pub fn set_jump_threshold(&mut self, threshold: Option<f32>) {
+        self.jump_threshold = threshold;
+    }
+    /// confines the selection to a box, `None` to let it move freely
+    pub fn set_bounds(&mut self, bounds: Option<MoveBounds>) {
+        self.bounds = bounds;
+    }
+    /// snaps the selection to a world aligned grid, `None` to not snap
+    pub fn set_snap(&mut self, snap: Option<SnapSettings>) {
+        self.snap = snap;
+        self.selection.set_snap(snap);
+    }
+    /// fields the selection stops at and slides along instead of passing through them,
+    /// each one is queried every update so keep the list short
+    pub fn set_obstacles(&mut self, obstacles: Vec<FieldRef>) {
+        self.obstacles = obstacles;
+    }
+    pub fn scale(&self) -> Vec3 {
+        self.scale
+    }
+    pub fn set_scale(&mut self, scale: Vec3) {
+        self.scale = scale;
+    }
+    pub fn selection(&self) -> &CapturedSelection {
+        &self.selection
+    }
+    /// stops moving the selection without letting go of it
+    pub fn into_selection(self) -> CapturedSelection {
+        self.selection
+    }
+    /// distance between the input and the selection in meters
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+    /// reels the selection in (negative) or pushes it out (positive) by `delta` meters,
+    /// within [`MIN_DISTANCE`] and [`MAX_DISTANCE`] of the input.
+    /// selections captured closer or further than that can still be moved towards the range
+    /// without jumping into it
+    pub fn adjust_distance(&mut self, delta: f32) {
+        let distance = adjusted_distance(self.target_distance, delta);
+        if distance == self.target_distance {
+            return;
+        }
+        self.target_distance = distance;
+        _ = self
+            .target
+            .set_local_transform(Transform::from_translation(Vec3::NEG_Z * distance));
+    }
+    /// velocity of the selection relative to the client root in m/s
+    pub fn velocity(&self) -> Vec3A {
+        self.velocity.velocity
+    }
+    /// last known position of the selection relative to the client root
+    pub fn position(&self) -> Option<Vec3A> {
+        self.velocity.last_pose.map(|(pos, _, _)| pos)
+    }
+    /// lets go of the selection, it keeps moving with the velocity it had
+    /// (capped at `max_speed` m/s) until [`Throw::update`] slows it down to a stop
+    pub fn release(self, max_speed: f32) -> Throw {
+        Throw {
+            velocity: self.velocity.velocity.clamp_length_max(max_speed),
+            angular_velocity: self
+                .velocity
+                .angular_velocity
+                .clamp_length_max(MAX_THROW_SPIN),
+            selection: self.selection,
+        }
+    }
+    async fn track_velocity(&mut self) {
         let sel = self.selection.spatial();
-        let sel_transform = sel.get_transform(&self.input).await.unwrap();
-        let target_transform = self.target.get_transform(&self.input).await.unwrap();
+        let Ok(transform) = sel.get_transform(sel.client().get_root()).await else {
+            return;
+        };
+        let Some(pos) = transform.translation.map(Vec3A::from) else {
+            return;
+        };
+        let rot = transform.rotation.map(Quat::from).unwrap_or_default();
+        self.velocity.sample(pos, rot, Instant::now());
+    }
+    /// clamps a translation relative to the input into the bounds
+    async fn clamp_to_bounds(&self, translation: Vec3) -> Vec3 {
+        let Some(bounds) = &self.bounds else {
+            return translation;
+        };
+        let input_transform = match &bounds.space {
+            Some(space) => self.input.get_transform(space).await,
+            None => {
+                self.input
+                    .get_transform(self.input.client().get_root())
+                    .await
+            }
+        };
+        let Ok(input_transform) = input_transform else {
+            return translation;
+        };
+        let input_to_bounds = to_mat4(&input_transform);
+        let point = input_to_bounds.transform_point3(translation);
+        let clamped = point.clamp(bounds.min, bounds.max);
+        if clamped == point {
+            return translation;
+        }
+        input_to_bounds.inverse().transform_point3(clamped)
+    }
+    /// snaps a pose relative to the input on the world aligned grid,
+    /// `rotation` is `None` when it's not up to the mover
+    async fn snap_in_root(
+        &self,
+        snap: &SnapSettings,
+        translation: Vec3,
+        rotation: Option<Quat>,
+    ) -> (Vec3, Option<Quat>) {
+        let Ok(input_transform) = self
+            .input
+            .get_transform(self.input.client().get_root())
+            .await
+        else {
+            return (translation, rotation);
+        };
+        let input_to_root = to_mat4(&input_transform);
+        let input_rotation = input_transform.rotation.map(Quat::from).unwrap_or_default();
+        let (root_translation, root_rotation) = snap.snap(
+            input_to_root.transform_point3(translation),
+            input_rotation * rotation.unwrap_or_default(),
+        );
+        (
+            input_to_root.inverse().transform_point3(root_translation),
+            rotation.map(|_| input_rotation.inverse() * root_rotation),
+        )
+    }
+    /// cuts the move from `from` to `to` (relative to the input) short where the selection's
+    /// bounding box would enter an obstacle, the rest of the move slides along its surface
+    async fn collide(&self, from: Vec3, to: Vec3, rotation: Quat) -> Vec3 {
+        let movement = to - from;
+        let length = movement.length();
+        if self.obstacles.is_empty() || length < f32::EPSILON {
+            return to;
+        }
+        let direction = movement / length;
+        let (bounds_center, size) = self.selection.bounds();
+        let start = from + (rotation * bounds_center);
+        // how far the box reaches along the movement from its center
+        let extent = ((rotation.inverse() * direction).abs() * (size / 2.0)).element_sum();
+        let mut allowed = length;
+        let mut contact_normal = None;
+        for obstacle in &self.obstacles {
+            let Some(contact) =
+                trace_surface(obstacle, &self.input, start, direction, length + extent).await
+            else {
+                continue;
+            };
+            let reach = (contact.distance(start) - extent).max(0.0);
+            if reach < allowed {
+                allowed = reach;
+                contact_normal = obstacle
+                    .normal(&self.input, contact)
+                    .await
+                    .ok()
+                    .map(|normal| Vec3::from(normal).normalize_or_zero());
+            }
+        }
+        let Some(normal) = contact_normal else {
+            return from + (direction * allowed);
+        };
+        // only the part of the remaining move into the surface is blocked
+        let remaining = direction * (length - allowed);
+        let slide = remaining - (normal * remaining.dot(normal).min(0.0));
+        from + (direction * allowed) + slide
+    }
+    /// pushes a translation relative to the input out of the head's clearance sphere
+    async fn keep_clear_of_head(&self, translation: Vec3) -> Vec3 {
+        let Some((head, clearance)) = &self.head else {
+            return translation;
+        };
+        let Ok(Some(head_position)) = head
+            .get_transform(&self.input)
+            .await
+            .map(|t| t.translation.map(Vec3::from))
+        else {
+            return translation;
+        };
+        let offset = translation - head_position;
+        if offset.length() >= *clearance {
+            return translation;
+        }
+        head_position + (offset.normalize_or(Vec3::NEG_Z) * *clearance)
+    }
+    /// `delta` is the time since the last update in seconds
+    pub async fn update(&mut self, delta: f32) {
+        self.track_velocity().await;
+        let sel = self.selection.spatial();
+        // the input or the selection can go away at any time, the solver drops the mover then
+        let Ok(sel_transform) = sel.get_transform(&self.input).await else {
+            return;
+        };
+        let Ok(target_transform) = self.target.get_transform(&self.input).await else {
+            return;
+        };
         let sel_translation = sel_transform
             .translation
             .map(Vec3A::from)
@@ -51,20 +616,363 @@ impl Mover {
             .rotation
             .map(Quat::from)
             .unwrap_or_default();
-        let lerp_factor = 0.95;
-        let sel_len = sel_translation.length();
-        let target_len = target_translation.length();
-        let sel_quat = Quat::from_rotation_arc(Vec3::NEG_Z, sel_translation.normalize().into());
-        let target_quat =
-            Quat::from_rotation_arc(Vec3::NEG_Z, target_translation.normalize().into());
-        let quat = target_quat.slerp(sel_quat, lerp_factor);
-        let len = target_len.lerp(sel_len, lerp_factor);
-        let translation = (quat * Vec3::NEG_Z) * len;
-        let rotation = target_rotation.slerp(sel_rotation, lerp_factor);
+        let target_rotation = match self.rotation_mode {
+            RotationMode::Relative => target_rotation * self.grab_rotation,
+            RotationMode::Follow | RotationMode::Fixed => target_rotation,
+        };
+        let jumped = is_jump(
+            self.last_translation,
+            sel_translation.into(),
+            self.jump_threshold,
+        );
+        let delta = delta.clamp(MIN_DELTA, MAX_DELTA);
+        self.distance = sel_translation.length();
+        let (moved_translation, followed_rotation) = step_motion(
+            self.motion,
+            &mut self.spring,
+            jumped,
+            (sel_translation, sel_rotation),
+            (target_translation, target_rotation),
+            delta,
+        );
+        let translation = self
+            .constraint
+            .constrain_translation(sel_translation.into(), moved_translation);
+        let translation = self
+            .collide(sel_translation.into(), translation, sel_rotation)
+            .await;
+        let translation = self.keep_clear_of_head(translation).await;
+        let translation = self.clamp_to_bounds(translation).await;
+        let input_rotation = match self.rotation_mode {
+            RotationMode::Fixed => match self.input.get_transform(sel.client().get_root()).await {
+                Ok(input_transform) => input_transform.rotation.map(Quat::from).unwrap_or_default(),
+                Err(_) => return,
+            },
+            RotationMode::Follow | RotationMode::Relative => Quat::IDENTITY,
+        };
+        let rotation = step_rotation(
+            self.rotation_mode,
+            &self.constraint,
+            self.capture_rotation,
+            (sel_rotation, followed_rotation),
+            input_rotation,
+        );
+        // a fixed rotation isn't up to the mover, so it isn't snapped either
+        let fixed = self.rotation_mode == RotationMode::Fixed;
+        let (translation, rotation) = match &self.snap {
+            Some(snap) if !snap.on_release_only => {
+                let (translation, snapped) = self
+                    .snap_in_root(snap, translation, (!fixed).then_some(rotation))
+                    .await;
+                (translation, snapped.unwrap_or(rotation))
+            }
+            _ => (translation, rotation),
+        };
+        // a broken transform can leave the object unrecoverable, better to skip a frame
+        if !translation.is_finite() || !rotation.is_finite() {
+            warn!(?translation, ?rotation, "not applying non-finite transform");
+            return;
+        }
+        self.last_translation = Some(translation);
         sel.set_relative_transform(
             &self.input,
-            Transform::from_translation_rotation_scale(translation, rotation, Vec3::ONE),
+            Transform::from_translation_rotation_scale(translation, rotation, self.scale),
         )
         .unwrap();
     }
 }
+
+/// moves the selection towards the target for `delta` seconds of `motion`, both relative to
+/// the input. the selection swings around the input towards the target's direction, so it
+/// doesn't cut corners, and its distance from the input follows on its own.
+/// returns the selection's new translation and rotation
+fn step_motion(
+    motion: MoverMotion,
+    spring: &mut SpringState,
+    jumped: bool,
+    (sel_translation, sel_rotation): (Vec3A, Quat),
+    (target_translation, target_rotation): (Vec3A, Quat),
+    delta: f32,
+) -> (Vec3, Quat) {
+    let sel_len = sel_translation.length();
+    let target_len = target_translation.length();
+    // a selection right at the input has no direction, treat it as straight ahead
+    let sel_quat = Quat::from_rotation_arc(
+        Vec3::NEG_Z,
+        sel_translation.normalize_or(Vec3A::NEG_Z).into(),
+    );
+    let target_quat = Quat::from_rotation_arc(
+        Vec3::NEG_Z,
+        target_translation.normalize_or(Vec3A::NEG_Z).into(),
+    );
+    let (quat, len, rotation) = match motion {
+        _ if jumped => {
+            *spring = SpringState::default();
+            (target_quat, target_len, target_rotation)
+        }
+        MoverMotion::Lerp { position, rotation } => {
+            let position_factor = position.powf(delta * REFERENCE_RATE);
+            let rotation_factor = rotation.powf(delta * REFERENCE_RATE);
+            (
+                target_quat.slerp(sel_quat, position_factor),
+                target_len.lerp(sel_len, position_factor),
+                target_rotation.slerp(sel_rotation, rotation_factor),
+            )
+        }
+        MoverMotion::Spring { stiffness, damping } => {
+            let params = (stiffness, damping);
+            (
+                spring_quat(sel_quat, target_quat, &mut spring.direction, params, delta),
+                spring_f32(sel_len, target_len, &mut spring.distance, params, delta),
+                spring_quat(
+                    sel_rotation,
+                    target_rotation,
+                    &mut spring.rotation,
+                    params,
+                    delta,
+                ),
+            )
+        }
+    };
+    ((quat * Vec3::NEG_Z) * len, rotation)
+}
+
+/// the selection's rotation relative to the input after an update that turned it from
+/// `sel_rotation` to `followed_rotation`. a [`RotationMode::Fixed`] selection instead gets
+/// `capture_rotation` re-asserted in the world every update, so it stays put no matter how
+/// the input (rotated by `input_rotation` in the world) moves
+fn step_rotation(
+    mode: RotationMode,
+    constraint: &MoveConstraint,
+    capture_rotation: Quat,
+    (sel_rotation, followed_rotation): (Quat, Quat),
+    input_rotation: Quat,
+) -> Quat {
+    match mode {
+        RotationMode::Follow | RotationMode::Relative => {
+            constraint.constrain_rotation(sel_rotation, followed_rotation)
+        }
+        RotationMode::Fixed => rotation_in_frame(capture_rotation, input_rotation),
+    }
+}
+
+/// `current` moved by `delta`, kept within [`MIN_DISTANCE`] and [`MAX_DISTANCE`] or
+/// wherever `current` already is outside of them
+fn adjusted_distance(current: f32, delta: f32) -> f32 {
+    (current + delta).clamp(MIN_DISTANCE.min(current), MAX_DISTANCE.max(current))
+}
+
+/// the rotation relative to a frame rotated by `frame_rotation` that has `world_rotation`
+/// relative to the world (whatever `frame_rotation` is relative to)
+fn rotation_in_frame(world_rotation: Quat, frame_rotation: Quat) -> Quat {
+    frame_rotation.inverse() * world_rotation
+}
+
+pub(crate) fn to_mat4(transform: &Transform) -> Mat4 {
+    Mat4::from_scale_rotation_translation(
+        transform.scale.map(Vec3::from).unwrap_or(Vec3::ONE),
+        transform.rotation.map(Quat::from).unwrap_or_default(),
+        transform.translation.map(Vec3::from).unwrap_or_default(),
+    )
+}
+
+/// a released selection that's still coasting, see [`Mover::release`]
+pub struct Throw {
+    selection: CapturedSelection,
+    // m/s, relative to the client root
+    velocity: Vec3A,
+    // rotation axis scaled by radians/s, relative to the client root
+    angular_velocity: Vec3A,
+}
+impl Throw {
+    /// velocity relative to the client root in m/s
+    pub fn velocity(&self) -> Vec3A {
+        self.velocity
+    }
+    pub fn selection(&self) -> &CapturedSelection {
+        &self.selection
+    }
+    /// stops the throw where it is without letting go of the selection
+    pub fn into_selection(self) -> CapturedSelection {
+        self.selection
+    }
+    /// once this is `false` the throw can be dropped to fully release the selection
+    pub fn is_moving(&self) -> bool {
+        self.velocity.length() > MIN_THROW_SPEED
+    }
+    /// moves the selection along by `delta` seconds, `drag` is how much of the velocity is
+    /// lost per second (1/s)
+    pub async fn update(&mut self, delta: f32, drag: f32) {
+        let sel = self.selection.spatial();
+        let root = sel.client().get_root();
+        let Ok(transform) = sel.get_transform(root).await else {
+            self.velocity = Vec3A::ZERO;
+            return;
+        };
+        let translation =
+            transform.translation.map(Vec3A::from).unwrap_or_default() + (self.velocity * delta);
+        let rotation = Quat::from_scaled_axis((self.angular_velocity * delta).into())
+            * transform.rotation.map(Quat::from).unwrap_or_default();
+        _ = sel.set_relative_transform(
+            root,
+            Transform::from_translation_rotation(Vec3::from(translation), rotation),
+        );
+        let decay = (-drag.max(0.0) * delta).exp();
+        self.velocity *= decay;
+        self.angular_velocity *= decay;
+    }
+}
+
+/// samples the ballistic path starting at `origin` with `velocity`, gravity pulls along -Y
+pub fn predict_trajectory(
+    origin: Vec3,
+    velocity: Vec3,
+    gravity: f32,
+    duration: f32,
+    segments: usize,
+) -> Vec<Vec3> {
+    (0..=segments)
+        .map(|i| {
+            let t = duration * (i as f32 / segments as f32);
+            origin + (velocity * t) + (Vec3::NEG_Y * gravity * 0.5 * t * t)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// runs the rotation part of [`Mover::update`] while the input moves and turns,
+    /// returns the selection's world rotation after every update
+    fn turn_input(mode: RotationMode, captured: Quat) -> Vec<Quat> {
+        let constraint = MoveConstraint::RotationLockedTo(Vec3::Y);
+        let mut spring = SpringState::default();
+        let mut world = captured;
+        let mut worlds = Vec::new();
+        for step in 0..32 {
+            let t = step as f32 * 0.1;
+            let input = Quat::from_euler(EulerRot::YXZ, t, t * 0.5, -t * 0.25);
+            // the selection relative to the moved input, and a target that turns with it
+            let sel = (Vec3A::new(0.0, t * 0.1, -0.5), input.inverse() * world);
+            let target = (Vec3A::new(0.1, 0.0, -0.6), Quat::from_rotation_x(0.3));
+            let (_, followed) = step_motion(
+                MoverMotion::default(),
+                &mut spring,
+                false,
+                sel,
+                target,
+                0.011,
+            );
+            let rotation = step_rotation(mode, &constraint, captured, (sel.1, followed), input);
+            world = input * rotation;
+            worlds.push(world);
+        }
+        worlds
+    }
+
+    #[test]
+    fn fixed_rotation_stays_put_while_the_input_turns() {
+        let captured = Quat::from_euler(EulerRot::YXZ, 0.4, -0.3, 0.2);
+        for world in turn_input(RotationMode::Fixed, captured) {
+            assert!(
+                world.abs_diff_eq(captured, 1e-5),
+                "{world:?} drifted from {captured:?}"
+            );
+        }
+        // following the input does turn the selection in the same situation
+        let followed = turn_input(RotationMode::Follow, captured);
+        assert!(!followed.last().unwrap().abs_diff_eq(captured, 1e-2));
+    }
+
+    #[test]
+    fn mass_scales_the_configured_motion() {
+        let spring = MoverMotion::critically_damped(200.0);
+        let MoverMotion::Spring { stiffness, damping } = spring.with_mass(50.0) else {
+            panic!("a spring turned into a lerp");
+        };
+        assert!(stiffness < 200.0);
+        // still critically damped
+        assert!((damping - (2.0 * stiffness.sqrt())).abs() < 1e-3);
+        let MoverMotion::Lerp { position, rotation } = (MoverMotion::Lerp {
+            position: 0.9,
+            rotation: 0.97,
+        })
+        .with_mass(0.0) else {
+            panic!("a lerp turned into a spring");
+        };
+        // lighter follows faster, and position and rotation keep their own rates
+        assert!(position < 0.9 && rotation < 0.97 && position < rotation);
+        // the mass matching the default rate leaves the motion as configured
+        let default_mass = 9.0;
+        assert!((rate_for_mass(default_mass) - DEFAULT_RATE).abs() < 1e-6);
+        let MoverMotion::Spring { stiffness, .. } = spring.with_mass(default_mass) else {
+            panic!("a spring turned into a lerp");
+        };
+        assert!((stiffness - 200.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn selection_at_the_input_stays_finite() {
+        let motions = [
+            MoverMotion::Lerp {
+                position: DEFAULT_RATE,
+                rotation: DEFAULT_RATE,
+            },
+            MoverMotion::critically_damped(200.0),
+        ];
+        let at_input = (Vec3A::ZERO, Quat::IDENTITY);
+        let targets = [at_input, (Vec3A::NEG_Z * 0.5, Quat::from_rotation_y(1.0))];
+        for motion in motions {
+            for target in targets {
+                for jumped in [false, true] {
+                    let mut spring = SpringState::default();
+                    let (translation, rotation) =
+                        step_motion(motion, &mut spring, jumped, at_input, target, MIN_DELTA);
+                    assert!(translation.is_finite(), "{motion:?} {translation:?}");
+                    assert!(rotation.is_finite(), "{motion:?} {rotation:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn adjusting_distance_never_jumps() {
+        assert_eq!(adjusted_distance(1.0, -2.0), MIN_DISTANCE);
+        assert_eq!(adjusted_distance(1.0, 10.0), MAX_DISTANCE);
+        // captured closer than the minimum, pushing it out works and reeling in doesn't snap it out
+        assert_eq!(adjusted_distance(0.05, -0.01), 0.05);
+        assert!((adjusted_distance(0.05, 0.02) - 0.07).abs() < 1e-6);
+        // captured further than the maximum
+        assert_eq!(adjusted_distance(8.0, 1.0), 8.0);
+        assert_eq!(adjusted_distance(8.0, -1.0), 7.0);
+    }
+
+    #[test]
+    fn handed_off_velocity_continues() {
+        let velocity = Vec3A::new(1.0, 0.0, -0.5);
+        let start = Instant::now();
+        let step = std::time::Duration::from_millis(11);
+        let mut tracker = VelocityTracker::default();
+        let sample = |tracker: &mut VelocityTracker, i: u32| {
+            let t = step * i;
+            tracker.sample(velocity * t.as_secs_f32(), Quat::IDENTITY, start + t);
+        };
+        for i in 0..30 {
+            sample(&mut tracker, i);
+        }
+        assert!(tracker.velocity.abs_diff_eq(velocity, 1e-3));
+        // what `Mover::hand_off` carries over to the new mover
+        let mut handed_off = tracker.handed_off();
+        assert_eq!(handed_off.velocity, tracker.velocity);
+        let mut fresh = VelocityTracker::default();
+        for i in 30..32 {
+            sample(&mut handed_off, i);
+            sample(&mut fresh, i);
+            // no step in the velocity right after the hand off
+            assert!(handed_off.velocity.abs_diff_eq(velocity, 1e-3));
+        }
+        // without carrying it over the selection would lose half its speed
+        assert!(fresh.velocity.length() < velocity.length() * 0.6);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use stardust_xr_fusion::input::{InputData, InputDataType, Joint};
+
+use crate::{
+    ring::{ShakeDetector, ShakeSettings, pointer_attach_pose, tip_attach_pose},
+    solver::{GestureHand, SpreadThresholds},
+};
+
+/// one line of a recording, the input the solver used during a frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// seconds since the recording started
+    pub time: f32,
+    /// `None` while the solver had no input
+    pub input: Option<RecordedInput>,
+}
+
+/// the parts of [`InputData`] the solver looks at, relative to the ring's input handler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedInput {
+    Hand {
+        right: bool,
+        thumb_tip: RecordedJoint,
+        index_tip: RecordedJoint,
+        middle_tip: RecordedJoint,
+        palm: RecordedJoint,
+        wrist: RecordedJoint,
+    },
+    Tip {
+        origin: [f32; 3],
+        orientation: [f32; 4],
+        /// the `grab` datamap value
+        grab: f32,
+    },
+    Pointer {
+        origin: [f32; 3],
+        orientation: [f32; 4],
+        /// the `select` datamap value
+        select: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RecordedJoint {
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub radius: f32,
+}
+impl From<&Joint> for RecordedJoint {
+    fn from(joint: &Joint) -> Self {
+        Self {
+            position: joint.position.into(),
+            rotation: joint.rotation.into(),
+            radius: joint.radius,
+        }
+    }
+}
+
+impl From<&InputData> for RecordedInput {
+    fn from(data: &InputData) -> Self {
+        match &data.input {
+            InputDataType::Hand(hand) => RecordedInput::Hand {
+                right: hand.right,
+                thumb_tip: (&hand.thumb.tip).into(),
+                index_tip: (&hand.index.tip).into(),
+                middle_tip: (&hand.middle.tip).into(),
+                palm: (&hand.palm).into(),
+                wrist: (&hand.wrist).into(),
+            },
+            InputDataType::Tip(tip) => RecordedInput::Tip {
+                origin: tip.origin.into(),
+                orientation: tip.orientation.into(),
+                grab: data.datamap.with_data(|d| d.idx("grab").as_f32()),
+            },
+            InputDataType::Pointer(pointer) => RecordedInput::Pointer {
+                origin: pointer.origin.into(),
+                orientation: pointer.orientation.into(),
+                select: data.datamap.with_data(|d| d.idx("select").as_f32()),
+            },
+        }
+    }
+}
+
+/// writes the solver's input to a file as JSON lines, one [`RecordedFrame`] per frame
+pub struct InputRecorder {
+    file: BufWriter<File>,
+    started: Instant,
+}
+impl InputRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+            started: Instant::now(),
+        })
+    }
+    pub fn record(&mut self, input: Option<&InputData>) -> io::Result<()> {
+        let frame = RecordedFrame {
+            time: self.started.elapsed().as_secs_f32(),
+            input: input.map(RecordedInput::from),
+        };
+        serde_json::to_writer(&mut self.file, &frame)?;
+        self.file.write_all(b"\n")
+    }
+}
+
+/// reads a recording written by [`InputRecorder`], see [`replay_gestures`]
+pub fn load_recording(path: &Path) -> io::Result<Vec<RecordedFrame>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter(|line| line.as_ref().is_ok_and(|line| !line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// what the gesture recognition made of a recorded frame, see [`replay_gestures`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayedFrame {
+    /// the input levitates (hands) or holds its grab/select button (tips and pointers)
+    pub levitating: bool,
+    /// levitating started this frame, objects get captured
+    pub started: bool,
+    /// levitating stopped this frame, held objects get let go of
+    pub stopped: bool,
+    /// shaking the input detached the ring from it this frame,
+    /// see [`RingSettings::shake_detach`](crate::ring::RingSettings::shake_detach)
+    pub detached: bool,
+}
+
+/// feeds a recording through the same levitation gesture recognition
+/// [`Solver::frame`](crate::solver::Solver::frame) uses, with the solver's
+/// [`SolverSettings::spread`](crate::solver::SolverSettings::spread) and
+/// [`SolverSettings::palm_facing_angle`](crate::solver::SolverSettings::palm_facing_angle),
+/// and through the ring's shake detection with `shake_detach`
+/// (see [`RingSettings::shake_detach`](crate::ring::RingSettings::shake_detach)).
+///
+/// a recording only holds the input the ring is attached to, so attaching the ring by grabbing
+/// it with another input can't be replayed, and neither can anything else that needs a server
+/// (selecting and moving objects)
+pub fn replay_gestures(
+    frames: &[RecordedFrame],
+    spread: &SpreadThresholds,
+    palm_facing_angle: f32,
+    shake_detach: Option<&ShakeSettings>,
+) -> Vec<ReplayedFrame> {
+    let mut acting = false;
+    let mut shake = ShakeDetector::default();
+    let started = Instant::now();
+    frames
+        .iter()
+        .map(|frame| {
+            let now = started + Duration::from_secs_f32(frame.time.max(0.0));
+            let detached =
+                frame
+                    .input
+                    .as_ref()
+                    .zip(shake_detach)
+                    .is_some_and(|(input, settings)| {
+                        shake.update(attach_position(input), now, settings)
+                    });
+            if detached {
+                shake = ShakeDetector::default();
+            }
+            let levitating = frame.input.as_ref().is_some_and(|input| match input {
+                RecordedInput::Hand {
+                    right,
+                    thumb_tip,
+                    index_tip,
+                    middle_tip,
+                    palm,
+                    ..
+                } => GestureHand {
+                    right: *right,
+                    thumb_tip: Vec3::from_array(thumb_tip.position),
+                    thumb_radius: thumb_tip.radius,
+                    index_tip: Vec3::from_array(index_tip.position),
+                    index_radius: index_tip.radius,
+                    middle_tip: Vec3::from_array(middle_tip.position),
+                    palm_position: Vec3::from_array(palm.position),
+                    palm_rotation: Quat::from_array(palm.rotation),
+                }
+                .levitating(acting, spread, palm_facing_angle),
+                RecordedInput::Tip { grab, .. } => *grab > 0.5,
+                RecordedInput::Pointer { select, .. } => *select > 0.5,
+            });
+            let replayed = ReplayedFrame {
+                levitating,
+                started: levitating && !acting,
+                stopped: !levitating && acting,
+                detached,
+            };
+            acting = levitating;
+            replayed
+        })
+        .collect()
+}
+
+/// where the ring sits on a recorded input, like it does while attached to the live one
+fn attach_position(input: &RecordedInput) -> Vec3 {
+    match input {
+        RecordedInput::Hand { wrist, .. } => Vec3::from_array(wrist.position),
+        RecordedInput::Tip {
+            origin,
+            orientation,
+            ..
+        } => tip_attach_pose(Vec3::from_array(*origin), Quat::from_array(*orientation)).0,
+        RecordedInput::Pointer {
+            origin,
+            orientation,
+            ..
+        } => pointer_attach_pose(Vec3::from_array(*origin), Quat::from_array(*orientation)).0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joint(position: [f32; 3]) -> RecordedJoint {
+        RecordedJoint {
+            position,
+            rotation: Quat::IDENTITY.to_array(),
+            radius: 0.008,
+        }
+    }
+
+    /// a right hand with its palm facing down and thumb and index tip `spread` apart
+    fn hand_frame(time: f32, spread: f32) -> RecordedFrame {
+        let gap = spread + 0.016;
+        RecordedFrame {
+            time,
+            input: Some(RecordedInput::Hand {
+                right: true,
+                thumb_tip: joint([-0.02, -0.03, -0.06]),
+                index_tip: joint([-0.02 + gap, -0.03, -0.06]),
+                middle_tip: joint([0.01, -0.03, -0.09]),
+                palm: joint([0.0, 0.0, 0.0]),
+                wrist: joint([0.0, 0.0, 0.05]),
+            }),
+        }
+    }
+
+    #[test]
+    fn replays_spread_and_release() {
+        let spread = SpreadThresholds {
+            enter: 0.03,
+            exit: 0.02,
+        };
+        let frames = [
+            hand_frame(0.0, 0.0),
+            hand_frame(0.1, 0.025),
+            hand_frame(0.2, 0.04),
+            // between the thresholds, keeps levitating
+            hand_frame(0.3, 0.025),
+            hand_frame(0.4, 0.01),
+            RecordedFrame {
+                time: 0.5,
+                input: None,
+            },
+        ];
+        // the recording round trips through its file format
+        let frames: Vec<RecordedFrame> = frames
+            .iter()
+            .map(|frame| serde_json::from_str(&serde_json::to_string(frame).unwrap()).unwrap())
+            .collect();
+        let replayed = replay_gestures(&frames, &spread, std::f32::consts::PI, None);
+        let levitating = replayed
+            .iter()
+            .map(|frame| frame.levitating)
+            .collect::<Vec<_>>();
+        assert_eq!(levitating, [false, false, true, true, false, false]);
+        assert!(replayed[2].started && !replayed[3].started);
+        assert!(replayed[4].stopped && !replayed[5].stopped);
+    }
+
+    #[test]
+    fn pointer_select_levitates() {
+        let frame = |select| RecordedFrame {
+            time: 0.0,
+            input: Some(RecordedInput::Pointer {
+                origin: [0.0; 3],
+                orientation: Quat::IDENTITY.to_array(),
+                select,
+            }),
+        };
+        let replayed = replay_gestures(
+            &[frame(0.0), frame(1.0), frame(0.0)],
+            &SpreadThresholds::default(),
+            std::f32::consts::PI,
+            None,
+        );
+        assert_eq!(
+            replayed,
+            [
+                ReplayedFrame::default(),
+                ReplayedFrame {
+                    levitating: true,
+                    started: true,
+                    stopped: false,
+                    detached: false,
+                },
+                ReplayedFrame {
+                    levitating: false,
+                    started: false,
+                    stopped: true,
+                    detached: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn shaking_detaches_the_ring() {
+        // a pointer swung left and right every 20ms, well over the minimum speed
+        let frames = [0.0, 0.05, 0.0, 0.05, 0.0, 0.05]
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| RecordedFrame {
+                time: i as f32 * 0.02,
+                input: Some(RecordedInput::Pointer {
+                    origin: [x, 0.0, 0.0],
+                    orientation: Quat::IDENTITY.to_array(),
+                    select: 0.0,
+                }),
+            })
+            .collect::<Vec<_>>();
+        let detached = |shake_detach: Option<&ShakeSettings>| {
+            replay_gestures(
+                &frames,
+                &SpreadThresholds::default(),
+                std::f32::consts::PI,
+                shake_detach,
+            )
+            .iter()
+            .map(|frame| frame.detached)
+            .collect::<Vec<_>>()
+        };
+        // the third reversal detaches, after that shaking starts over
+        assert_eq!(
+            detached(Some(&ShakeSettings::default())),
+            [false, false, false, false, true, false]
+        );
+        assert!(!detached(None).contains(&true));
+        let slow = ShakeSettings {
+            min_speed: 10.0,
+            ..Default::default()
+        };
+        assert!(!detached(Some(&slow)).contains(&true));
+    }
+}
@@ -1,7 +1,13 @@
 use core::f32;
-use std::{process, sync::Arc};
+use std::{
+    collections::VecDeque,
+    process,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use glam::{Quat, Vec3, Vec3A};
+use serde::{Deserialize, Serialize};
 use stardust_xr_fusion::{
     ClientHandle,
     drawable::{Line, LinePoint, Lines, LinesAspect},
@@ -10,39 +16,229 @@ use stardust_xr_fusion::{
     node::NodeResult,
     root::FrameInfo,
     spatial::{Spatial, SpatialAspect, Transform},
-    values::color::rgba,
+    values::{Color, color::rgba},
     zbus::Connection,
 };
 use stardust_xr_molecules::{
-    Derezzable, FrameSensitive, Grabbable, GrabbableSettings, PointerMode, UIElement,
+    Derezzable, FrameSensitive, Grabbable, GrabbableSettings, MomentumSettings, PointerMode,
+    UIElement,
     input_action::{InputQueue, InputQueueable},
     lines::{LineExt, circle},
 };
+use tracing::warn;
+
+/// how long after a restart the ring waits for the input it was attached to before giving up
+const RESTORE_ATTACH_TIMEOUT: Duration = Duration::from_secs(5);
+/// inputs closer to each other than this (m) are equally close to the ring
+const ATTACH_TIE_DISTANCE: f32 = 0.001;
+/// furthest (m) the attach point of an input is extrapolated, so sudden turns don't overshoot
+const MAX_ATTACH_PREDICTION: f32 = 0.05;
+
+#[derive(Clone)]
+pub struct RingSettings {
+    /// radius of the ring (m), inputs have to be this close to the ring to attach it
+    pub radius: f32,
+    /// radius of the ring's tube (m)
+    pub thickness: f32,
+    /// how long an input has to be the best attach candidate before releasing the ring attaches it
+    pub attach_dwell: Duration,
+    /// radius of the sphere inputs have to be in to attach the ring
+    pub input_field_radius: f32,
+    /// offset of the attach sphere from the ring center, must not be behind the ring (+Z)
+    pub input_field_offset: Vec3,
+    /// draw the attach sphere
+    pub debug_input_field: bool,
+    /// color of the ring itself
+    pub ring_color: Color,
+    /// color of the line to the input the ring is about to attach to
+    pub attach_line_color: Color,
+    /// tint the ring with the system accent color instead of `ring_color`
+    pub use_accent_color: bool,
+    /// shaking the input the ring is attached to detaches it, `None` to only detach by grabbing
+    pub shake_detach: Option<ShakeSettings>,
+    /// how far ahead inputs moving towards the ring are extrapolated for the attach line,
+    /// `None` to draw it to where the input is
+    pub attach_prediction: Option<Duration>,
+    /// inputs whose extrapolated position is close enough to the ring can attach it too
+    pub predictive_attach: bool,
+    /// the ring keeps sliding after being flicked and let go of until it settles,
+    /// `None` to stop it where it was let go
+    pub linear_momentum: Option<MomentumSettings>,
+    /// the ring keeps spinning after being flicked and let go of until it settles,
+    /// `None` to stop it turning where it was let go
+    pub angular_momentum: Option<MomentumSettings>,
+    /// snap the ring to the grabbing input instead of keeping the offset it was grabbed at
+    pub magnet: bool,
+    /// another input grabbing the ring while it's attached attaches too instead of detaching it,
+    /// grabbing it again with that input detaches only that one, see [`Ring::get_secondary_input`]
+    pub bimanual: bool,
+}
+impl Default for RingSettings {
+    fn default() -> Self {
+        Self {
+            radius: 0.05,
+            thickness: 0.005,
+            attach_dwell: Duration::ZERO,
+            input_field_radius: 0.1,
+            input_field_offset: Vec3::new(0.0, 0.0, -0.05),
+            debug_input_field: false,
+            ring_color: rgba!(1.0, 1.0, 1.0, 1.0).to_linear(),
+            attach_line_color: rgba!(0.7, 0.7, 0.7, 1.0).to_linear(),
+            use_accent_color: false,
+            shake_detach: None,
+            attach_prediction: Some(Duration::from_millis(50)),
+            predictive_attach: false,
+            linear_momentum: None,
+            angular_momentum: None,
+            magnet: false,
+            bimanual: false,
+        }
+    }
+}
+
+/// how an input moved recently, see [`RingSettings::attach_prediction`]
+struct InputMotion {
+    method_ref: InputMethodRef,
+    position: Vec3,
+    // m/s, relative to the ring's input handler
+    velocity: Vec3,
+    time: Instant,
+}
+
+/// what counts as shaking the input, see [`RingSettings::shake_detach`]
+#[derive(Debug, Clone, Copy)]
+pub struct ShakeSettings {
+    /// the input has to move at least this fast (m/s) for a change of direction to count
+    pub min_speed: f32,
+    /// how many times the input has to turn around, a single fast swing back doesn't detach
+    pub reversals: usize,
+    /// all reversals have to happen within this long
+    pub window: Duration,
+}
+impl Default for ShakeSettings {
+    fn default() -> Self {
+        Self {
+            min_speed: 1.5,
+            reversals: 3,
+            window: Duration::from_millis(600),
+        }
+    }
+}
+
+/// counts fast reversals of direction of the attached input
+#[derive(Debug, Default)]
+pub(crate) struct ShakeDetector {
+    last_position: Option<(Vec3, Instant)>,
+    // direction of the last movement faster than `ShakeSettings::min_speed`
+    last_direction: Option<Vec3>,
+    reversals: VecDeque<Instant>,
+}
+impl ShakeDetector {
+    /// returns whether the input has been shaken, `position` is where it is at `now`
+    pub(crate) fn update(
+        &mut self,
+        position: Vec3,
+        now: Instant,
+        settings: &ShakeSettings,
+    ) -> bool {
+        let Some((last_position, last_time)) = self.last_position.replace((position, now)) else {
+            return false;
+        };
+        let delta = now.duration_since(last_time).as_secs_f32();
+        if delta <= 0.0 {
+            return false;
+        }
+        let velocity = (position - last_position) / delta;
+        if velocity.length() < settings.min_speed {
+            return false;
+        }
+        let direction = velocity.normalize();
+        // roughly opposite, anything less is just a curved swing
+        if self
+            .last_direction
+            .is_some_and(|last_direction| last_direction.dot(direction) < -0.5)
+        {
+            self.reversals.push_back(now);
+        }
+        self.last_direction = Some(direction);
+        while self
+            .reversals
+            .front()
+            .is_some_and(|reversal| now.duration_since(*reversal) > settings.window)
+        {
+            self.reversals.pop_front();
+        }
+        self.reversals.len() >= settings.reversals
+    }
+}
+
+/// what the ring remembers across restarts, see [`Ring::state`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RingState {
+    /// relative to the client root
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub attached_to: Option<InputKind>,
+}
+
+/// input methods don't keep their identity across restarts,
+/// so the ring reattaches to the first input of the same kind instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputKind {
+    LeftHand,
+    RightHand,
+    Tip,
+    Pointer,
+}
+impl InputKind {
+    pub fn of(input: &InputData) -> Self {
+        match &input.input {
+            InputDataType::Hand(hand) if hand.right => InputKind::RightHand,
+            InputDataType::Hand(_) => InputKind::LeftHand,
+            InputDataType::Tip(_) => InputKind::Tip,
+            InputDataType::Pointer(_) => InputKind::Pointer,
+        }
+    }
+}
 
 pub struct Ring {
+    settings: RingSettings,
     grabbable: Grabbable,
     derezzable: Derezzable,
-    _grabbable_lines: Lines,
+    grabbable_lines: Lines,
+    grabbable_line: Line,
     _input_field: Field,
+    _input_field_lines: Option<Lines>,
     pub input: InputQueue,
     attached_to: Option<InputMethodRef>,
+    // the second input while attached to two, see [`RingSettings::bimanual`]
+    secondary: Option<InputMethodRef>,
+    // kind of the input the ring was attached to before a restart and when the ring was restored
+    restore_attach: Option<(InputKind, Instant)>,
+    // the current best attach candidate and since when it has been the best
+    attach_candidate: Option<(InputMethodRef, Instant)>,
     attach_lines: Lines,
+    shake: ShakeDetector,
+    input_motion: Vec<InputMotion>,
 }
 impl Ring {
-    pub fn new(conn: Connection, client: &Arc<ClientHandle>) -> NodeResult<Self> {
+    pub fn new(
+        conn: Connection,
+        client: &Arc<ClientHandle>,
+        settings: RingSettings,
+        state: Option<RingState>,
+    ) -> NodeResult<Self> {
         let spatial = Spatial::create(client.get_root(), Transform::none())?;
-        let grab_radius = 0.05;
-        let grab_thickness = 0.005;
         let grabbable_shape = Shape::Torus(stardust_xr_fusion::fields::TorusShape {
-            radius_a: grab_radius,
-            radius_b: grab_thickness,
+            radius_a: settings.radius,
+            radius_b: settings.thickness,
         });
         let grabbable_field = Field::create(
             &spatial,
             Transform::from_rotation(Quat::from_rotation_x(f32::consts::FRAC_PI_2)),
             grabbable_shape.clone(),
         )?;
-        let grabbable = Grabbable::create(
+        let mut grabbable = Grabbable::create(
             conn.clone(),
             "/Ring",
             &spatial,
@@ -50,25 +246,50 @@ impl Ring {
             &grabbable_field,
             GrabbableSettings {
                 max_distance: 0.03,
-                linear_momentum: None,
-                angular_momentum: None,
-                magnet: false,
+                linear_momentum: settings.linear_momentum,
+                angular_momentum: settings.angular_momentum,
+                magnet: settings.magnet,
                 pointer_mode: PointerMode::Align,
                 reparentable: true,
             },
         )?;
+        if let Some(state) = &state {
+            grabbable.set_pose(
+                Vec3::from_array(state.position),
+                Quat::from_array(state.rotation),
+            );
+        }
         let grabbable_spatial = grabbable.content_parent();
         grabbable_field.set_spatial_parent(&grabbable_spatial)?;
-        let _grabbable_lines = Lines::create(
+        let grabbable_line = circle(32, 0.0, settings.radius).thickness(settings.thickness);
+        let grabbable_lines = Lines::create(
             &grabbable_spatial,
             Transform::from_rotation(Quat::from_rotation_x(f32::consts::FRAC_PI_2)),
-            &[circle(32, 0.0, grab_radius).thickness(grab_thickness)],
+            &[grabbable_line.clone().color(settings.ring_color)],
         )?;
+        let mut input_field_offset = settings.input_field_offset;
+        if input_field_offset.z > 0.0 {
+            warn!(
+                ?input_field_offset,
+                "ring input field offset is behind the ring, moving it to the ring's plane"
+            );
+            input_field_offset.z = 0.0;
+        }
         let input_field = Field::create(
             &grabbable_spatial,
-            Transform::from_translation([0.0, 0.0, -0.05]),
-            Shape::Sphere(0.1),
+            Transform::from_translation(input_field_offset),
+            Shape::Sphere(settings.input_field_radius),
         )?;
+        let input_field_lines = settings
+            .debug_input_field
+            .then(|| {
+                Lines::create(
+                    &grabbable_spatial,
+                    Transform::from_translation(input_field_offset),
+                    &sphere_outline(settings.input_field_radius),
+                )
+            })
+            .transpose()?;
         let input = InputHandler::create(&spatial, Transform::none(), &input_field)?.queue()?;
         let attach_lines = Lines::create(&spatial, Transform::none(), &[])?;
         let derezzable = Derezzable::create(
@@ -78,13 +299,23 @@ impl Ring {
             Some(grabbable_field.clone()),
         )?;
         Ok(Ring {
+            settings,
             grabbable,
-            _grabbable_lines,
+            grabbable_lines,
+            grabbable_line,
             _input_field: input_field,
+            _input_field_lines: input_field_lines,
             input,
             attached_to: None,
+            secondary: None,
+            restore_attach: state
+                .and_then(|state| state.attached_to)
+                .map(|kind| (kind, Instant::now())),
+            attach_candidate: None,
             attach_lines,
             derezzable,
+            shake: ShakeDetector::default(),
+            input_motion: Vec::new(),
         })
     }
     pub fn update(&mut self, frame_info: &FrameInfo) {
@@ -97,34 +328,74 @@ impl Ring {
             return;
         }
         self.grabbable.frame(frame_info);
+        if self.attached_to.is_none()
+            && let Some((kind, since)) = self.restore_attach.take()
+            && since.elapsed() < RESTORE_ATTACH_TIMEOUT
+        {
+            let restored = self
+                .input
+                .input()
+                .into_iter()
+                .find(|(input, _)| InputKind::of(input) == kind)
+                .map(|(_, method_ref)| method_ref.clone());
+            match restored {
+                Some(method_ref) => _ = self.attach_to(method_ref),
+                None => self.restore_attach = Some((kind, since)),
+            }
+        }
         if self.grabbable.grab_action().actor_started() && self.attached_to.is_some() {
-            self.on_detach();
+            let grabbed_by = self
+                .grabbable
+                .grab_action()
+                .actor()
+                .and_then(|actor| self.method_of(actor));
+            match grabbed_by {
+                Some(method_ref) if self.secondary.as_ref() == Some(&method_ref) => {
+                    _ = self.detach_secondary();
+                }
+                Some(method_ref)
+                    if self.settings.bimanual
+                        && self.secondary.is_none()
+                        && self.attached_to.as_ref() != Some(&method_ref) =>
+                {
+                    _ = self.attach_secondary(method_ref);
+                }
+                _ => _ = self.detach(),
+            }
         }
 
+        self.track_input_motion();
         let pos = self.grabbable.pose().0.into();
-        let attaching_to = self.get_input_to_capture(pos);
+        let attaching_to = self
+            .get_input_to_capture(pos)
+            .map(|(input, method_ref)| (input, method_ref.clone()));
+        match attaching_to.as_ref() {
+            Some((_, method_ref))
+                if self
+                    .attach_candidate
+                    .as_ref()
+                    .is_some_and(|(candidate, _)| candidate == method_ref) => {}
+            Some((_, method_ref)) => {
+                self.attach_candidate = Some((method_ref.clone(), Instant::now()));
+            }
+            None => self.attach_candidate = None,
+        }
         if self.grabbable.grab_action().actor_acting()
-            && let Some((attaching_to, _)) = attaching_to.as_ref()
+            && self.attached_to.is_none()
+            && let Some((attaching_to, method_ref)) = attaching_to.as_ref()
         {
-            let point = match &attaching_to.input {
-                InputDataType::Pointer(_) => Vec3::ZERO,
-                InputDataType::Tip(tip) => {
-                    let quat = Quat::from(tip.orientation);
-                    Vec3::from(tip.origin) + quat.mul_vec3(Vec3::Z * 0.05)
-                }
-                InputDataType::Hand(hand) => Vec3::from(hand.wrist.position),
-            };
+            let point = self.predicted_attach_point(attaching_to, method_ref);
             _ = self.attach_lines.set_lines(&[Line {
                 points: vec![
                     LinePoint {
                         point: point.into(),
                         thickness: 0.005,
-                        color: rgba!(0.7, 0.7, 0.7, 1.0).to_linear(),
+                        color: self.settings.attach_line_color,
                     },
                     LinePoint {
                         point: pos.into(),
                         thickness: 0.005,
-                        color: rgba!(0.7, 0.7, 0.7, 1.0).to_linear(),
+                        color: self.settings.attach_line_color,
                     },
                 ],
                 cyclic: false,
@@ -133,57 +404,288 @@ impl Ring {
             _ = self.attach_lines.set_lines(&[]);
         }
         if self.grabbable.grab_action().actor_stopped()
+            && self.attached_to.is_none()
             && let Some((_, method_ref)) = attaching_to
+            && self
+                .attach_candidate
+                .as_ref()
+                .is_some_and(|(_, since)| since.elapsed() >= self.settings.attach_dwell)
         {
-            self.on_attach(method_ref.clone());
+            _ = self.attach_to(method_ref);
         }
         if let Some(input) = self.get_attached_input() {
-            let (pos, rot) = match &input.input {
-                InputDataType::Pointer(_) => (Vec3::ZERO, Quat::IDENTITY),
-                InputDataType::Tip(tip) => {
-                    let quat = Quat::from(tip.orientation);
-                    (Vec3::from(tip.origin) + quat.mul_vec3(Vec3::Z * 0.05), quat)
-                }
-                InputDataType::Hand(hand) => {
-                    (hand.wrist.position.into(), Quat::from(hand.wrist.rotation))
-                }
-            };
+            let (pos, rot) = attach_pose(&input);
+            // leftover momentum from being let go of would drift the ring off the input
+            self.grabbable.cancel_linear_velocity();
+            self.grabbable.cancel_angular_velocity();
             self.grabbable.set_pose(pos, rot);
+            if let Some(shake) = &self.settings.shake_detach
+                && self.shake.update(pos, Instant::now(), shake)
+            {
+                _ = self.detach();
+            }
+        }
+    }
+    /// attaches the ring to `method_ref` like grabbing the ring onto it would,
+    /// detaching it from the input it was attached to first.
+    /// the ring stays detached when the input can't be captured
+    pub fn attach_to(&mut self, method_ref: InputMethodRef) -> NodeResult<()> {
+        if self.attached_to.as_ref() == Some(&method_ref) {
+            return Ok(());
         }
+        _ = self.detach();
+        method_ref.try_capture(self.input.handler())?;
+        self.attached_to = Some(method_ref);
+        Ok(())
     }
-    fn on_attach(&mut self, method_ref: InputMethodRef) {
-        if method_ref.try_capture(self.input.handler()).is_ok() {
-            self.attached_to = Some(method_ref);
+    /// detaches the ring from its inputs, the ring counts as detached even when releasing fails
+    pub fn detach(&mut self) -> NodeResult<()> {
+        self.shake = ShakeDetector::default();
+        let secondary = self.detach_secondary();
+        match self.attached_to.take() {
+            Some(method_ref) => method_ref.release(self.input.handler()),
+            None => Ok(()),
         }
+        .and(secondary)
     }
-    fn on_detach(&mut self) {
-        if let Some(method_ref) = self.attached_to.take() {
-            _ = method_ref.release(self.input.handler());
+    fn attach_secondary(&mut self, method_ref: InputMethodRef) -> NodeResult<()> {
+        method_ref.try_capture(self.input.handler())?;
+        self.secondary = Some(method_ref);
+        Ok(())
+    }
+    fn detach_secondary(&mut self) -> NodeResult<()> {
+        match self.secondary.take() {
+            Some(method_ref) => method_ref.release(self.input.handler()),
+            None => Ok(()),
+        }
+    }
+    /// where the ring is relative to the client root
+    pub fn position(&self) -> Vec3 {
+        self.grabbable.pose().0.into()
+    }
+    pub fn is_attached(&self) -> bool {
+        self.attached_to.is_some()
+    }
+    /// the ring's pose and what it's attached to, to restore it with after a restart
+    pub fn state(&self) -> RingState {
+        let (position, rotation) = self.grabbable.pose();
+        RingState {
+            position: Vec3::from(position).to_array(),
+            rotation: Quat::from(rotation).to_array(),
+            attached_to: self.get_attached_input().map(|input| InputKind::of(&input)),
+        }
+    }
+    /// rebuilds the ring on a new session bus connection after the old one was lost,
+    /// in the same place and reattaching to an input of the same kind like after a restart
+    pub fn reconnect(&mut self, conn: Connection) -> NodeResult<()> {
+        let mut state = self.state();
+        state.attached_to = state
+            .attached_to
+            .or(self.restore_attach.map(|(kind, _)| kind));
+        let client = self.grabbable_lines.client().clone();
+        _ = self.detach();
+        *self = Ring::new(conn, &client, self.settings.clone(), Some(state))?;
+        Ok(())
+    }
+    /// tints the ring with the accent color if [`RingSettings::use_accent_color`] is set
+    pub fn set_accent_color(&mut self, color: Color) {
+        if self.settings.use_accent_color {
+            _ = self
+                .grabbable_lines
+                .set_lines(&[self.grabbable_line.clone().color(color)]);
+        }
+    }
+    /// hides the ring and stops it from being grabbed or capturing inputs.
+    ///
+    /// disabling releases the inputs the ring is attached to, enabling it again reattaches
+    /// it to an input of the same kind like after a restart.
+    /// [`Ring::update`] shouldn't be called while disabled
+    pub fn set_enabled(&mut self, enabled: bool) {
+        _ = self.grabbable_lines.set_enabled(enabled);
+        _ = self.attach_lines.set_lines(&[]);
+        _ = self.grabbable.set_enabled(enabled);
+        _ = self.input.handler().set_enabled(enabled);
+        self.attach_candidate = None;
+        if enabled {
+            // the restore timeout counts from when the ring is back
+            if let Some((_, since)) = &mut self.restore_attach {
+                *since = Instant::now();
+            }
+        } else {
+            if let Some(input) = self.get_attached_input() {
+                self.restore_attach = Some((InputKind::of(&input), Instant::now()));
+            }
+            _ = self.detach();
         }
     }
     pub fn get_attached_input(&self) -> Option<Arc<InputData>> {
-        self.attached_to
-            .as_ref()
-            .and_then(|attached| {
-                self.input
-                    .input()
-                    .into_iter()
-                    .find(|(_, method)| *method == attached)
-            })
+        self.input_of(self.attached_to.as_ref()?)
+    }
+    /// the second input the ring is attached to, only ever set with [`RingSettings::bimanual`]
+    pub fn get_secondary_input(&self) -> Option<Arc<InputData>> {
+        self.input_of(self.secondary.as_ref()?)
+    }
+    fn input_of(&self, method_ref: &InputMethodRef) -> Option<Arc<InputData>> {
+        self.input
+            .input()
+            .into_iter()
+            .find(|(_, method)| *method == method_ref)
             .map(|(i, _)| i)
     }
-    fn get_input_to_capture(&self, pos: Vec3A) -> Option<(Arc<InputData>, &InputMethodRef)> {
+    /// input data from other handlers (e.g. the grabbable's) carries the same id
+    fn method_of(&self, input: &InputData) -> Option<InputMethodRef> {
         self.input
             .input()
             .into_iter()
-            .filter(|(i, _)| match &i.input {
-                InputDataType::Pointer(_) => false,
-                InputDataType::Tip(tip) => {
-                    let quat = Quat::from(tip.orientation);
-                    pos.distance(Vec3A::from(tip.origin) + quat.mul_vec3a(Vec3A::Z * 0.05)) < 0.05
+            .find(|(i, _)| i.id == input.id)
+            .map(|(_, method)| method.clone())
+    }
+    fn track_input_motion(&mut self) {
+        let now = Instant::now();
+        let mut input_motion = Vec::new();
+        for (input, method_ref) in self.input.input() {
+            let position = attach_pose(&input).0;
+            let velocity = self
+                .input_motion
+                .iter()
+                .find(|motion| motion.method_ref == *method_ref)
+                .map(|last| {
+                    let delta = now.duration_since(last.time).as_secs_f32();
+                    if delta <= 0.0 {
+                        return last.velocity;
+                    }
+                    // smooth out tracking jitter
+                    last.velocity.lerp((position - last.position) / delta, 0.5)
+                })
+                .unwrap_or_default();
+            input_motion.push(InputMotion {
+                method_ref: method_ref.clone(),
+                position,
+                velocity,
+                time: now,
+            });
+        }
+        self.input_motion = input_motion;
+    }
+    /// where the input attaches the ring, extrapolated along its recent motion
+    fn predicted_attach_point(&self, input: &InputData, method_ref: &InputMethodRef) -> Vec3 {
+        let point = attach_pose(input).0;
+        let Some(lookahead) = self.settings.attach_prediction else {
+            return point;
+        };
+        let velocity = self
+            .input_motion
+            .iter()
+            .find(|motion| motion.method_ref == *method_ref)
+            .map(|motion| motion.velocity)
+            .unwrap_or_default();
+        point + (velocity * lookahead.as_secs_f32()).clamp_length_max(MAX_ATTACH_PREDICTION)
+    }
+    /// the input closest to the ring, inputs about as close as the current attach candidate
+    /// don't take over from it so the attach line doesn't jump between them
+    fn get_input_to_capture(&self, pos: Vec3A) -> Option<(Arc<InputData>, &InputMethodRef)> {
+        let is_candidate = |method_ref: &InputMethodRef| {
+            self.attach_candidate
+                .as_ref()
+                .is_some_and(|(candidate, _)| candidate == method_ref)
+        };
+        let inputs = self
+            .input
+            .input()
+            .into_iter()
+            .map(|(i, method_ref)| {
+                let mut distance = pos.distance(attach_pose(&i).0.into());
+                if self.settings.predictive_attach {
+                    distance = distance
+                        .min(pos.distance(self.predicted_attach_point(&i, method_ref).into()));
                 }
-                InputDataType::Hand(hand) => pos.distance(hand.wrist.position.into()) < 0.05,
+                (distance, (i, method_ref))
+            })
+            .filter(|(distance, _)| *distance < self.settings.radius);
+        closest_input(inputs, |(_, method_ref)| is_candidate(method_ref)).map(|(_, input)| input)
+    }
+}
+
+/// the closest of the `(distance, input)` pairs. among inputs within [`ATTACH_TIE_DISTANCE`]
+/// of each other the current attach candidate wins no matter the order they come in,
+/// so the choice doesn't flip between frames
+fn closest_input<T>(
+    inputs: impl IntoIterator<Item = (f32, T)>,
+    is_candidate: impl Fn(&T) -> bool,
+) -> Option<(f32, T)> {
+    inputs.into_iter().reduce(|a, b| {
+        if (a.0 - b.0).abs() < ATTACH_TIE_DISTANCE {
+            if is_candidate(&b.1) { b } else { a }
+        } else if a.0 < b.0 {
+            a
+        } else {
+            b
+        }
+    })
+}
+
+/// where the ring sits on an input it's attached to
+fn attach_pose(input: &InputData) -> (Vec3, Quat) {
+    match &input.input {
+        InputDataType::Pointer(pointer) => {
+            pointer_attach_pose(pointer.origin.into(), pointer.orientation.into())
+        }
+        InputDataType::Tip(tip) => tip_attach_pose(tip.origin.into(), tip.orientation.into()),
+        InputDataType::Hand(hand) => (hand.wrist.position.into(), Quat::from(hand.wrist.rotation)),
+    }
+}
+
+/// a bit in front of the pointer so it stays visible
+pub(crate) fn pointer_attach_pose(origin: Vec3, orientation: Quat) -> (Vec3, Quat) {
+    (
+        origin + orientation.mul_vec3(Vec3::NEG_Z * 0.05),
+        orientation,
+    )
+}
+
+pub(crate) fn tip_attach_pose(origin: Vec3, orientation: Quat) -> (Vec3, Quat) {
+    (origin + orientation.mul_vec3(Vec3::Z * 0.05), orientation)
+}
+
+/// three circles around the origin, one in each plane
+fn sphere_outline(radius: f32) -> [Line; 3] {
+    let outline = circle(32, 0.0, radius).thickness(0.001);
+    let swizzled = |swizzle: fn(Vec3) -> Vec3| Line {
+        points: outline
+            .points
+            .iter()
+            .map(|p| LinePoint {
+                point: swizzle(p.point.into()).into(),
+                ..p.clone()
             })
-            .reduce(|a, b| if a.0.distance < b.0.distance { a } else { b })
+            .collect(),
+        cyclic: outline.cyclic,
+    };
+    [
+        swizzled(|p| p),
+        swizzled(|p| Vec3::new(p.x, p.z, p.y)),
+        swizzled(|p| Vec3::new(p.z, p.y, p.x)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equidistant_inputs_keep_the_candidate() {
+        let is_candidate = |input: &&str| *input == "right";
+        let inputs = [(0.02, "left"), (0.02 + ATTACH_TIE_DISTANCE * 0.5, "right")];
+        let forward = closest_input(inputs, is_candidate);
+        let reversed = closest_input(inputs.into_iter().rev(), is_candidate);
+        assert_eq!(forward.map(|(_, input)| input), Some("right"));
+        assert_eq!(reversed.map(|(_, input)| input), Some("right"));
+        // a clearly closer input still wins over the candidate
+        let closer = [(0.01, "left"), (0.02, "right")];
+        assert_eq!(
+            closest_input(closer, is_candidate).map(|(_, input)| input),
+            Some("left")
+        );
+        assert_eq!(closest_input(Vec::<(f32, &str)>::new(), is_candidate), None);
     }
 }
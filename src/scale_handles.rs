@@ -0,0 +1,256 @@
+use glam::Vec3;
+use stardust_xr_fusion::{
+    drawable::{Line, LinePoint, Lines},
+    fields::{Field, Shape},
+    input::{InputDataType, InputHandler},
+    node::NodeResult,
+    spatial::{Spatial, Transform},
+    values::color::rgba_linear,
+};
+use stardust_xr_molecules::input_action::{InputQueue, InputQueueable};
+
+use crate::selection::Vec3Component;
+
+/// length of each handle marker's arms (m)
+pub const HANDLE_SIZE: f32 = 0.01;
+/// how close a pinch has to be to a handle to grab it (m)
+pub const GRAB_DISTANCE: f32 = 0.03;
+/// thumb and index tips closer than this count as a pinch (m)
+pub const PINCH_DISTANCE: f32 = 0.015;
+/// thumb and index tip separation (m) a [`PinchScale`] drag measures its start against at
+/// least, so a pinch with touching tips doesn't make the tiniest spread a huge scale change
+const MIN_START_SEPARATION: f32 = 0.005;
+pub const MIN_SCALE: f32 = 0.05;
+pub const MAX_SCALE: f32 = 20.0;
+
+struct Drag {
+    axis: Vec3Component,
+    // distance of the pinch from the center along the axis when the drag started
+    start_offset: f32,
+    start_scale: Vec3,
+    // used to keep following the same hand
+    last_pinch: Vec3,
+}
+
+/// handles on the faces of a captured object's bounding box that can be pinched with the
+/// other hand to scale the object along that axis
+pub struct ScaleHandles {
+    _lines: Lines,
+    _field: Field,
+    input: InputQueue,
+    center: Vec3,
+    half_size: Vec3,
+    drag: Option<Drag>,
+}
+impl ScaleHandles {
+    /// `center` and `size` describe the bounding box relative to `parent`
+    pub fn create(parent: &Spatial, center: Vec3, size: Vec3) -> NodeResult<Self> {
+        let half_size = size * 0.5;
+        let handle_lines = handle_positions(center, half_size)
+            .flat_map(|(_, position)| {
+                Vec3Component::ALL.map(|component| {
+                    let offset = component.unit() * HANDLE_SIZE;
+                    Line {
+                        points: [position - offset, position + offset]
+                            .into_iter()
+                            .map(|point| LinePoint {
+                                point: point.into(),
+                                thickness: 0.002,
+                                color: rgba_linear!(1.0, 1.0, 1.0, 1.0),
+                            })
+                            .collect(),
+                        cyclic: false,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let lines = Lines::create(parent, Transform::none(), &handle_lines)?;
+        let field = Field::create(
+            parent,
+            Transform::from_translation(center),
+            Shape::Box((size + Vec3::splat(GRAB_DISTANCE * 2.0)).into()),
+        )?;
+        let input = InputHandler::create(parent, Transform::none(), &field)?.queue()?;
+        Ok(ScaleHandles {
+            _lines: lines,
+            _field: field,
+            input,
+            center,
+            half_size,
+            drag: None,
+        })
+    }
+
+    /// whether a handle is being dragged
+    pub fn dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+    /// returns the new scale while a handle is being dragged,
+    /// new drags only start with `may_start` so they don't fight another way of scaling
+    pub fn update(&mut self, scale: Vec3, may_start: bool) -> Option<Vec3> {
+        self.input.handle_events();
+        let pinches = pinches(&self.input, scale);
+
+        if let Some(drag) = &mut self.drag {
+            let Some(pinch) = pinches.iter().copied().min_by(|a, b| {
+                a.distance_squared(drag.last_pinch)
+                    .total_cmp(&b.distance_squared(drag.last_pinch))
+            }) else {
+                self.drag = None;
+                return None;
+            };
+            drag.last_pinch = pinch;
+            let offset = drag.axis.get((pinch - self.center) * scale).abs();
+            let factor = offset / drag.start_offset;
+            let axis = drag.axis.unit();
+            let new_axis_scale =
+                (drag.axis.get(drag.start_scale) * factor).clamp(MIN_SCALE, MAX_SCALE);
+            return Some((drag.start_scale * (Vec3::ONE - axis)) + (axis * new_axis_scale));
+        }
+        if !may_start {
+            return None;
+        }
+
+        let (axis, pinch) = pinches.iter().find_map(|pinch| {
+            handle_positions(self.center, self.half_size)
+                .find(|(_, handle)| ((*handle - *pinch) * scale).length() < GRAB_DISTANCE)
+                .map(|(axis, _)| (axis, *pinch))
+        })?;
+        let start_offset = axis.get((pinch - self.center) * scale).abs();
+        if start_offset <= f32::EPSILON {
+            return None;
+        }
+        self.drag = Some(Drag {
+            axis,
+            start_offset,
+            start_scale: scale,
+            last_pinch: pinch,
+        });
+        None
+    }
+}
+
+struct UniformDrag {
+    // thumb and index tip separation when the drag started
+    start_separation: f32,
+    start_scale: Vec3,
+    // used to keep following the same hand
+    last_position: Vec3,
+}
+
+/// pinching near a captured object with the other hand and then spreading thumb and index
+/// apart scales it up uniformly, closing them again scales it back down.
+///
+/// the drag follows the hand until it leaves the area around the object,
+/// the object keeps the size it had then
+pub struct PinchScale {
+    _field: Field,
+    input: InputQueue,
+    center: Vec3,
+    drag: Option<UniformDrag>,
+}
+impl PinchScale {
+    /// `center` and `size` describe the bounding box relative to `parent`
+    pub fn create(parent: &Spatial, center: Vec3, size: Vec3) -> NodeResult<Self> {
+        let field = Field::create(
+            parent,
+            Transform::from_translation(center),
+            Shape::Box((size + Vec3::splat(GRAB_DISTANCE * 2.0)).into()),
+        )?;
+        let input = InputHandler::create(parent, Transform::none(), &field)?.queue()?;
+        Ok(PinchScale {
+            _field: field,
+            input,
+            center,
+            drag: None,
+        })
+    }
+
+    /// whether a hand is scaling the object
+    pub fn dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+    /// returns the new scale while a hand is scaling the object,
+    /// new drags only start with `may_start` so they don't fight another way of scaling
+    pub fn update(&mut self, scale: Vec3, may_start: bool) -> Option<Vec3> {
+        self.input.handle_events();
+        let hands = hands(&self.input, scale);
+
+        if let Some(drag) = &mut self.drag {
+            let Some((position, separation)) = hands.iter().copied().min_by(|(a, _), (b, _)| {
+                a.distance_squared(drag.last_position)
+                    .total_cmp(&b.distance_squared(drag.last_position))
+            }) else {
+                self.drag = None;
+                return None;
+            };
+            drag.last_position = position;
+            return Some(uniform_scale(
+                drag.start_scale,
+                separation / drag.start_separation,
+            ));
+        }
+        if !may_start {
+            return None;
+        }
+
+        let (position, separation) = hands
+            .iter()
+            .copied()
+            .find(|(_, separation)| *separation < PINCH_DISTANCE)?;
+        self.drag = Some(UniformDrag {
+            start_separation: separation.max(MIN_START_SEPARATION),
+            start_scale: scale,
+            last_position: position,
+        });
+        None
+    }
+}
+
+/// `start_scale` scaled by `factor`, clamped as a whole so the object keeps its proportions
+fn uniform_scale(start_scale: Vec3, factor: f32) -> Vec3 {
+    start_scale
+        * factor.clamp(
+            MIN_SCALE / start_scale.min_element(),
+            MAX_SCALE / start_scale.max_element(),
+        )
+}
+
+/// midpoint between thumb and index tip relative to the handler's parent and their separation
+/// in meters for every hand.
+///
+/// input is relative to the scaled parent, so distances are scaled back into meters
+fn hands(input: &InputQueue, scale: Vec3) -> Vec<(Vec3, f32)> {
+    input
+        .input()
+        .into_iter()
+        .filter_map(|(data, _)| match &data.input {
+            InputDataType::Hand(hand) => {
+                let thumb = Vec3::from(hand.thumb.tip.position);
+                let index = Vec3::from(hand.index.tip.position);
+                Some((
+                    thumb.lerp(index, 0.5),
+                    (thumb * scale).distance(index * scale),
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// midpoints of all pinching hands relative to the handler's parent
+fn pinches(input: &InputQueue, scale: Vec3) -> Vec<Vec3> {
+    hands(input, scale)
+        .into_iter()
+        .filter(|(_, separation)| *separation < PINCH_DISTANCE)
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// the center of each bounding box face and the axis it scales
+fn handle_positions(center: Vec3, half_size: Vec3) -> impl Iterator<Item = (Vec3Component, Vec3)> {
+    Vec3Component::ALL.into_iter().flat_map(move |component| {
+        let offset = component.unit() * component.get(half_size);
+        [(component, center + offset), (component, center - offset)]
+    })
+}
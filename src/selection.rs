@@ -1,10 +1,19 @@
 use core::f32;
-use std::{f32::consts::FRAC_PI_2, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    f32::consts::FRAC_PI_2,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use glam::{Quat, Vec3};
+use futures::future::join_all;
+use glam::{Mat4, Quat, Vec3};
 use stardust_xr_fusion::{
     ClientHandle,
-    drawable::{Lines, LinesAspect, Model},
+    drawable::{Line, LinePoint, Lines, LinesAspect, Model, Text, TextAspect, TextStyle},
     fields::{FieldRef, FieldRefAspect},
     list_query::{ListEvent, ObjectListQuery},
     node::{NodeResult, NodeType},
@@ -14,29 +23,328 @@ use stardust_xr_fusion::{
     },
     query::ObjectQuery,
     spatial::{Spatial, SpatialAspect, SpatialRef, SpatialRefAspect, Transform},
+    values::{Color, color::rgba_linear},
+    zbus::Proxy,
 };
 use stardust_xr_molecules::{
     dbus::AbortOnDrop,
     lines::{LineExt, bounding_box},
 };
-use tracing::warn;
+use tokio::sync::{Notify, broadcast};
+use tracing::{Instrument, Span, debug, debug_span, field::Empty, info, instrument, warn};
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::mover::{SnapSettings, to_mat4};
+
+/// how long an object's cached bounds are trusted before they're queried again,
+/// objects moved by other clients may be missed by the broad phase for this long
+const BOUNDS_CACHE_LIFETIME: Duration = Duration::from_secs(1);
+/// extra room (m) around an object's bounding sphere before the broad phase rejects it,
+/// in case its field sticks out of its bounding box a bit
+const BROAD_PHASE_MARGIN: f32 = 0.05;
+
+/// how many captures [`Selector::undo_last_move`] can go back
+const UNDO_DEPTH: usize = 32;
+
+/// where [`Selector::duplicate_selected`] asks for the copy to appear, relative to the original
+const DUPLICATE_OFFSET: Vec3 = Vec3::new(0.05, 0.0, 0.05);
+
+/// see [`SelectorSettings::selection_cone`]
+pub const DEFAULT_SELECTION_CONE: f32 = 0.1;
+
+pub struct SelectorSettings {
+    /// when exactly one object is roughly in front of the ray, select it without precise aim
+    pub auto_select_single: bool,
+    /// cone slope (distance from ray per meter along the ray) objects without a field have to be
+    /// in to be selected, narrower cones tell apart objects in dense scenes and favor objects
+    /// right on the ray more. defaults to [`DEFAULT_SELECTION_CONE`] (about 5.7°)
+    pub selection_cone: f32,
+    /// objects further along the ray than this (m) can't be selected
+    pub max_range: f32,
+    /// cone slope (distance from ray per meter along the ray) used to find auto select candidates
+    pub auto_select_cone: f32,
+    /// how often to log how many captures failed because another client held the reparent lock,
+    /// `None` to never log
+    pub lock_contention_log_interval: Option<Duration>,
+    /// lock objects before reparenting them.
+    ///
+    /// disabling this skips a round trip on capture, but other clients can then grab and
+    /// reparent the same object at the same time, only disable it if nothing else moves your objects
+    pub use_reparent_lock: bool,
+    /// how often to try locking an object again before giving up on capturing it
+    pub lock_retries: u32,
+    /// wait before the first lock retry, each further retry waits this much longer
+    pub lock_retry_backoff: Duration,
+    /// how long capturing may wait for the object's lock (retries included) and for exporting
+    /// the spatial it's moved by, so an unresponsive client can't stall the frame loop
+    pub capture_timeout: Duration,
+    /// how the selection highlight is colored
+    pub highlight: HighlightStyle,
+    /// what happens to captured objects when they are let go
+    pub release: ReleaseSettings,
+    /// warn if the object registry hasn't reported a single object this long after startup,
+    /// `None` to never warn
+    pub registry_timeout: Option<Duration>,
+    /// smallest size of the selection highlight and solver target per axis (m),
+    /// so tiny objects stay visibly selected
+    pub min_highlight_size: f32,
+    /// how the solver target is sized to captured objects
+    pub target_fit: TargetFit,
+    /// largest size of the solver target per axis (m), so huge objects don't get a target
+    /// filling the room. wins over [`SelectorSettings::min_highlight_size`]
+    pub max_target_size: f32,
+    /// objects closer than this along the ray (m) are too close for the ray to aim at reliably,
+    /// while any are this close the one nearest to the ray origin is selected instead,
+    /// `0.0` to always select by aim
+    pub min_selection_distance: f32,
+    /// keep highlighting the last selected object when the ray briefly misses everything,
+    /// so jittery hand tracking doesn't make the highlight flicker
+    pub selection_grace: Duration,
+    /// how long an object has to stay highlighted before it can be captured, so sweeping the ray
+    /// across a cluttered scene doesn't grab whatever it passes. the highlight is dimmed until then
+    pub hover_dwell: Duration,
+    /// only objects this returns `true` for can be selected, `None` to allow any object.
+    /// it's called for every object every frame, so keep it cheap
+    pub filter: Option<SelectionFilter>,
+    /// how candidates hit by the ray are ranked against each other
+    pub scoring: SelectionScoring,
+    /// mark where the ray hits every candidate and label it with its score (lower wins),
+    /// the winner in green. nothing for it is created while this is off
+    pub debug_scores: bool,
+}
+impl Default for SelectorSettings {
+    fn default() -> Self {
+        Self {
+            auto_select_single: false,
+            selection_cone: DEFAULT_SELECTION_CONE,
+            max_range: f32::INFINITY,
+            auto_select_cone: 0.5,
+            lock_contention_log_interval: None,
+            use_reparent_lock: true,
+            lock_retries: 2,
+            lock_retry_backoff: Duration::from_millis(10),
+            capture_timeout: Duration::from_secs(1),
+            highlight: HighlightStyle::default(),
+            release: ReleaseSettings::default(),
+            registry_timeout: Some(Duration::from_secs(30)),
+            min_highlight_size: 0.02,
+            target_fit: TargetFit::default(),
+            max_target_size: 1.0,
+            min_selection_distance: 0.0,
+            selection_grace: Duration::from_millis(150),
+            hover_dwell: Duration::ZERO,
+            filter: None,
+            scoring: SelectionScoring::default(),
+            debug_scores: false,
+        }
+    }
+}
+
+/// ranks candidates by how far along the ray they are and how far their center is off the ray,
+/// lower scores win. both are in meters, the distance is measured to the deepest point of the ray
+/// inside fields, to where the ray enters bounding boxes, and to the origin of objects without either.
+///
+/// with the default 1:1 weights objects without bounds rank by `distance + offset` like they
+/// always have (with the offset scaled by [`SelectorSettings::selection_cone`]). fields and boxes
+/// are hit by the ray so their offset is bounded by their size, which keeps the nearest hit winning
+/// among them while breaking ties between overlapping objects in favor of the more centered one
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionScoring {
+    pub distance_weight: f32,
+    pub offset_weight: f32,
+}
+impl SelectionScoring {
+    fn score(&self, hit: RayHit) -> f32 {
+        (self.distance_weight * hit.distance) + (self.offset_weight * hit.off_axis)
+    }
+}
+impl Default for SelectionScoring {
+    fn default() -> Self {
+        Self {
+            distance_weight: 1.0,
+            offset_weight: 1.0,
+        }
+    }
+}
+
+/// see [`SelectorSettings::filter`]
+pub type SelectionFilter = Box<dyn Fn(&SpatialRef, Option<&FieldRef>) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HighlightStyle {
+    /// always white
+    Plain,
+    /// green when the ray passes right through the object's center,
+    /// fading to yellow towards the edge of what still selects it
+    #[default]
+    Confidence,
+    /// the system accent color, see [`Selector::set_accent_color`]
+    Accent,
+}
+
+/// see [`SelectorSettings::target_fit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetFit {
+    /// scaled evenly to the larger of the two axes across the object's longest one,
+    /// long thin objects get a target that's loose around their short axis
+    #[default]
+    Uniform,
+    /// stretched to the object's size along each axis so it hugs the object's bounds
+    PerAxis,
+}
+
+/// how captured objects are let go of.
+///
+/// released objects always go back to the parent their own client gave them, the one they had
+/// before being captured, since they're handed back with `unparent`. there is no setting to
+/// leave them at the world root instead: the reparentable interface neither tells us what an
+/// object's parent is nor lets us hand it to a parent of our choosing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReleaseSettings {
+    /// let released objects down onto the surface of a field below them,
+    /// `None` to leave them where they were let go
+    pub dock: Option<DockSettings>,
+}
+
+/// see [`Selector::dock_to_surface`]
+#[derive(Debug, Clone, Copy)]
+pub struct DockSettings {
+    /// which way is down
+    pub axis: DockAxis,
+    /// surfaces further away than this (m) are ignored
+    pub max_distance: f32,
+}
+impl Default for DockSettings {
+    fn default() -> Self {
+        Self {
+            axis: DockAxis::WorldDown,
+            max_distance: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DockAxis {
+    /// -Y of the client root
+    #[default]
+    WorldDown,
+    /// -Y of the object itself, e.g. to stick objects onto walls by tilting them
+    ObjectDown,
+}
+
+/// objects that can be deleted, like the ring itself
+#[zbus::proxy(interface = "org.stardustxr.Derezzable", gen_blocking = false)]
+trait Derezzable {
+    fn derez(&self) -> zbus::Result<()>;
+}
+
+/// objects that can spawn a copy of themselves, `offset` is relative to the object
+/// and the returned path is the copy's object path on the same connection
+#[zbus::proxy(interface = "org.stardustxr.Duplicatable", gen_blocking = false)]
+trait Duplicatable {
+    fn duplicate(&self, offset: (f64, f64, f64)) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// objects that tell how heavy they are, `mass` is in kg
+#[zbus::proxy(interface = "org.stardustxr.Mass", gen_blocking = false)]
+trait MassHint {
+    #[zbus(property)]
+    fn mass(&self) -> zbus::Result<f64>;
+}
+
+/// what happened to objects, see [`Selector::events`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverEvent {
+    /// the highlighted object changed, `None` when nothing is highlighted anymore
+    HoverChanged(Option<ObjectId>),
+    Captured(ObjectId),
+    Released(ObjectId),
+}
+
+/// an object from the registry that can be selected and reparented
+type Selectable = (
+    SpatialRef,
+    ReparentableProxy<'static>,
+    ReparentLockProxy<'static>,
+    Option<FieldRef>,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError {
+    /// the object isn't in the registry or can't be reparented
+    NotReparentable,
+    /// another client held the object's reparent lock, even after retrying
+    LockHeld,
+    /// the spatial to reparent the object to couldn't be created or exported
+    ExportFailed,
+    /// reparenting the object to the solver failed
+    ReparentFailed,
+    /// the object's bounding box couldn't be queried
+    BoundsUnavailable,
+    /// locking the object or exporting the capture spatial took longer than
+    /// [`SelectorSettings::capture_timeout`]
+    TimedOut,
+}
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::NotReparentable => write!(f, "object is not reparentable"),
+            CaptureError::LockHeld => write!(f, "object is locked by another client"),
+            CaptureError::ExportFailed => write!(f, "unable to export the capture spatial"),
+            CaptureError::ReparentFailed => write!(f, "unable to reparent object"),
+            CaptureError::BoundsUnavailable => write!(f, "unable to get the object's bounds"),
+            CaptureError::TimedOut => write!(f, "capturing the object timed out"),
+        }
+    }
+}
+impl std::error::Error for CaptureError {}
 
 pub struct Selector {
-    query: ObjectListQuery<(
-        SpatialRef,
-        ReparentableProxy<'static>,
-        ReparentLockProxy<'static>,
-        Option<FieldRef>,
-    )>,
+    settings: SelectorSettings,
+    query: ObjectListQuery<Selectable>,
     selection_lines: Lines,
-    selection: Option<(
-        SpatialRef,
-        ReparentableProxy<'static>,
-        ReparentLockProxy<'static>,
-        Option<FieldRef>,
-    )>,
+    selection: Option<Selectable>,
+    // lower is better, used to settle which selector gets to highlight a shared object
+    selection_score: f32,
+    // how centered the ray is on the selection, `None` when it wasn't selected by aim
+    selection_confidence: Option<f32>,
+    accent_color: Option<Color>,
+    // what the selection lines currently show, to skip sending the same lines again
+    highlighted: Option<Highlight>,
+    // when the ray last hit something
+    last_hit: Option<Instant>,
+    // since when the current selection has been selected
+    selected_since: Option<Instant>,
+    // objects added with `toggle_add_to_selection`, captured together as a group
+    group: Vec<Selectable>,
+    group_lines: Lines,
+    // object another selector has claimed the highlight for, see [`resolve_shared_highlight`]
+    excluded: Option<ObjectId>,
     target_model: Model,
+    // the captured object's local axes, shown along with the target model
+    axis_lines: Lines,
+    events: broadcast::Sender<SolverEvent>,
+    bounds_cache: BoundsCache,
+    lock_contention: LockContention,
+    // where captured objects were before they got captured, most recent last
+    undo: VecDeque<UndoEntry>,
+    // set by the mapper task when an object left the registry
+    object_lost: Arc<AtomicBool>,
+    // the registry's objects as of the last `refresh_objects`, so they aren't copied out of
+    // the query every frame
+    objects: Vec<(ObjectId, Selectable)>,
+    // set by the mapper task whenever an object was found, changed or lost
+    objects_changed: Arc<AtomicBool>,
+    // the mapper task reports a change before the query stores it,
+    // so the snapshot is taken once more on the refresh after the one that saw the change
+    refresh_again: bool,
+    // the object last duplicated and its copy, the copy takes over the highlight once it shows up
+    duplicate: Option<(ObjectId, ObjectId)>,
+    // id, distance along the ray and score of every object the ray hit in the last pass, best first
+    candidates: Vec<(ObjectId, f32, f32)>,
+    debug_overlay: Option<DebugOverlay>,
     _mapper_task: AbortOnDrop,
+    _registry_watchdog: Option<AbortOnDrop>,
 }
 
 impl Selector {
@@ -44,178 +352,1931 @@ impl Selector {
         client: Arc<ClientHandle>,
         object_registry: Arc<ObjectRegistry>,
         target_model: Model,
+        settings: SelectorSettings,
     ) -> NodeResult<Self> {
         let selection_lines = Lines::create(client.get_root(), Transform::none(), &[])?;
-        let (query, mapper) = ObjectQuery::<
-            (
-                SpatialRef,
-                ReparentableProxy<'static>,
-                ReparentLockProxy<'static>,
-                Option<FieldRef>,
-            ),
-            ClientHandle,
-        >::new(object_registry, client)
-        .to_list_query();
-        let mapper = tokio::spawn(mapper.init(async |e| match e {
-            ListEvent::NewMatch(v) => Some(v),
-            ListEvent::Modified(v) => Some(v),
-            ListEvent::MatchLost => None,
-            _ => None,
-        }));
+        let group_lines = Lines::create(client.get_root(), Transform::none(), &[])?;
+        let axis_lines = Lines::create(client.get_root(), Transform::none(), &[])?;
+        let debug_overlay = if settings.debug_scores {
+            Some(DebugOverlay {
+                lines: Lines::create(client.get_root(), Transform::none(), &[])?,
+                labels: Vec::new(),
+            })
+        } else {
+            None
+        };
+        let RegistryQuery {
+            query,
+            object_lost,
+            objects_changed,
+            mapper_task,
+            watchdog,
+        } = RegistryQuery::new(object_registry, client, settings.registry_timeout);
         Ok(Self {
+            settings,
             query,
-            _mapper_task: AbortOnDrop(mapper.abort_handle()),
+            _mapper_task: mapper_task,
+            debug_overlay,
+            _registry_watchdog: watchdog,
             selection_lines,
             selection: None,
+            selection_score: f32::INFINITY,
+            selection_confidence: None,
+            accent_color: None,
+            highlighted: None,
+            last_hit: None,
+            selected_since: None,
+            group: Vec::new(),
+            group_lines,
+            excluded: None,
             target_model,
+            axis_lines,
+            events: broadcast::channel(16).0,
+            bounds_cache: BoundsCache::default(),
+            lock_contention: LockContention::default(),
+            undo: VecDeque::new(),
+            object_lost,
+            objects: Vec::new(),
+            objects_changed,
+            refresh_again: false,
+            duplicate: None,
+            candidates: Vec::new(),
         })
     }
-    pub async fn capture_selected(&mut self) -> Option<CapturedSelection> {
-        let (spatial_ref, reparentable, reparent_lock, _) = self.selection.take()?;
-        if let Err(_) = reparent_lock.lock().await {
+    /// queries a new object registry, e.g. after the session bus connection was lost and the
+    /// registry had to be rebuilt on a new one.
+    ///
+    /// everything found through the old registry is forgotten, objects that are already
+    /// captured stay captured
+    pub fn reconnect(&mut self, object_registry: Arc<ObjectRegistry>) {
+        let RegistryQuery {
+            query,
+            object_lost,
+            objects_changed,
+            mapper_task,
+            watchdog,
+        } = RegistryQuery::new(
+            object_registry,
+            self.selection_lines.client().clone(),
+            self.settings.registry_timeout,
+        );
+        self.query = query;
+        self.object_lost = object_lost;
+        self.objects_changed = objects_changed;
+        self.objects.clear();
+        self._mapper_task = mapper_task;
+        self._registry_watchdog = watchdog;
+        self.clear_selection();
+        self.clear_group();
+        self.excluded = None;
+        self.duplicate = None;
+        self.candidates.clear();
+        self.bounds_cache = BoundsCache::default();
+    }
+    /// see [`SelectorSettings::selection_cone`], clamped to stay positive
+    pub fn set_selection_cone(&mut self, slope: f32) {
+        self.settings.selection_cone = slope.max(f32::EPSILON);
+    }
+    pub fn has_selection(&self) -> bool {
+        self.selection.is_some()
+    }
+    /// where the highlighted object is relative to the client root
+    pub async fn selection_position(&self) -> Option<Vec3> {
+        let (spatial, ..) = self.selection.as_ref()?;
+        spatial
+            .get_transform(self.selection_lines.client().get_root())
+            .await
+            .ok()?
+            .translation
+            .map(Vec3::from)
+    }
+    /// whether the selection has been highlighted for [`SelectorSettings::hover_dwell`]
+    pub fn selection_armed(&self) -> bool {
+        self.selected_since
+            .is_some_and(|since| since.elapsed() >= self.settings.hover_dwell)
+    }
+    /// every object the ray hit in the last [`Selector::update_selection`] and its score, best
+    /// (lowest) first. objects picked without aiming (see [`SelectorSettings::auto_select_single`]
+    /// and [`SelectorSettings::min_selection_distance`]) aren't scored, so the highlighted object,
+    /// see [`Selector::current_selection`], isn't necessarily the first one
+    pub fn candidates(&self) -> Vec<(ObjectId, f32)> {
+        self.candidates
+            .iter()
+            .map(|(id, _, score)| (id.clone(), *score))
+            .collect()
+    }
+    /// the highlighted object, the one a capture would pick up
+    pub fn current_selection(&self) -> Option<ObjectId> {
+        self.selection.as_ref().map(selectable_id)
+    }
+    /// hover, capture and release events, lagging or dropping receivers never blocks the selector
+    pub fn events(&self) -> broadcast::Receiver<SolverEvent> {
+        self.events.subscribe()
+    }
+    fn set_selection(&mut self, selection: Option<Selectable>) {
+        let id = selection.as_ref().map(selectable_id);
+        if id != self.current_selection() {
+            self.selected_since = id.as_ref().map(|_| Instant::now());
+            _ = self.events.send(SolverEvent::HoverChanged(id));
+        }
+        self.selection = selection;
+    }
+    /// drops the current highlight until the next [`Selector::update_selection`]
+    pub fn clear_selection(&mut self) {
+        self.set_selection(None);
+        self.selection_score = f32::INFINITY;
+        self.hide_highlight();
+    }
+    fn hide_highlight(&mut self) {
+        _ = self.selection_lines.set_lines(&[]);
+        // don't hang on to the object, the lines would go away along with it
+        _ = self
+            .selection_lines
+            .set_spatial_parent(self.selection_lines.client().get_root());
+        self.highlighted = None;
+    }
+    /// `None` when nothing is highlighted or it hasn't been highlighted for
+    /// [`SelectorSettings::hover_dwell`] yet, the highlight stays when the capture fails
+    #[instrument(level = "debug", skip_all)]
+    pub async fn capture_selected(&mut self) -> Option<Result<CapturedSelection, CaptureError>> {
+        self.forget_lost_objects().await;
+        if !self.selection_armed() {
             return None;
         }
+        let selection = self.selection.clone()?;
+        let captured = self.capture_object(selection, None).await;
+        if captured.is_ok() {
+            self.set_selection(None);
+        }
+        Some(captured)
+    }
+    /// redraws the highlight in red, e.g. after the highlighted object couldn't be captured.
+    /// the next [`Selector::update_selection`] draws it normally again
+    pub async fn highlight_failure(&mut self) {
+        let Some((spatial, ..)) = &self.selection else {
+            return;
+        };
+        let Ok(mut bb) = spatial
+            .get_relative_bounding_box(&self.selection_lines)
+            .await
+        else {
+            return;
+        };
+        bb.size = Vec3::from(bb.size)
+            .max(Vec3::splat(self.settings.min_highlight_size))
+            .into();
+        let lines = bounding_box(bb)
+            .into_iter()
+            .map(|l| l.thickness(0.0025).color(rgba_linear!(1.0, 0.0, 0.0, 1.0)))
+            .collect::<Vec<_>>();
+        _ = self.selection_lines.set_lines(&lines);
+        self.highlighted = None;
+    }
+    /// asks the highlighted object to derez (destroy) itself,
+    /// does nothing when nothing is highlighted
+    pub async fn delete_selected(&mut self) {
+        let Some(selection) = self.selection.clone() else {
+            return;
+        };
+        self.clear_selection();
+        let id = selectable_id(&selection);
+        self.group.retain(|member| selectable_id(member) != id);
+        let object = selection.1.inner();
+        let result: zbus::Result<()> = async {
+            DerezzableProxy::builder(object.connection())
+                .destination(object.destination().to_owned())?
+                .path(object.path().to_owned())?
+                .build()
+                .await?
+                .derez()
+                .await
+        }
+        .await;
+        if let Err(err) = result {
+            warn!(%id, %err, "unable to delete object");
+        }
+    }
+    /// asks the highlighted object to spawn a copy of itself next to it, the copy becomes the
+    /// selection as soon as it shows up in the registry.
+    ///
+    /// the registry only hands out spatials and reparenting interfaces, not whatever the object
+    /// was spawned from, so only objects implementing `org.stardustxr.Duplicatable` can be
+    /// duplicated. for any other object this just logs a warning
+    pub async fn duplicate_selected(&mut self) {
+        let Some(selection) = self.selection.clone() else {
+            return;
+        };
+        let id = selectable_id(&selection);
+        let object = selection.1.inner();
+        let offset = DUPLICATE_OFFSET.as_dvec3();
+        let result: zbus::Result<OwnedObjectPath> = async {
+            DuplicatableProxy::builder(object.connection())
+                .destination(object.destination().to_owned())?
+                .path(object.path().to_owned())?
+                .build()
+                .await?
+                .duplicate((offset.x, offset.y, offset.z))
+                .await
+        }
+        .await;
+        match result {
+            Ok(path) => {
+                let copy = ObjectId {
+                    destination: id.destination.clone(),
+                    path: path.to_string(),
+                };
+                self.duplicate = Some((id, copy));
+            }
+            Err(err) => warn!(%id, %err, "unable to duplicate object"),
+        }
+    }
+    /// rests `selection` on the nearest field below it with its up aligned to the surface normal,
+    /// see [`ReleaseSettings::dock`]. does nothing if docking is off or there's no surface in range
+    pub async fn dock_to_surface(&mut self, selection: &CapturedSelection) {
+        let Some(dock) = self.settings.release.dock else {
+            return;
+        };
         let root = self.selection_lines.client().get_root();
-        let spatial = Spatial::create(root, Transform::none()).ok()?;
-        spatial
-            .set_relative_transform(
-                &spatial_ref,
-                Transform {
-                    translation: Some([0.; 3].into()),
-                    rotation: Some(Quat::IDENTITY.into()),
-                    scale: None,
+        let spatial = selection.spatial();
+        let Ok(transform) = spatial.get_transform(root).await else {
+            return;
+        };
+        let rotation = transform.rotation.map(Quat::from).unwrap_or_default();
+        let (bounds_center, size) = selection.bounds();
+        let origin = to_mat4(&transform).transform_point3(bounds_center);
+        let down = match dock.axis {
+            DockAxis::WorldDown => Vec3::NEG_Y,
+            DockAxis::ObjectDown => rotation * Vec3::NEG_Y,
+        };
+        let own_ids = std::iter::once(selection.id())
+            .chain(selection.members().iter().map(CapturedSelection::id))
+            .cloned()
+            .collect::<HashSet<_>>();
+        self.refresh_objects().await;
+        let fields = self
+            .objects
+            .iter()
+            .filter(|(id, _)| !own_ids.contains(id))
+            .filter_map(|(_, (_, _, _, field))| field.clone())
+            .collect::<Vec<_>>();
+        let hits = join_all(
+            fields
+                .iter()
+                .map(|field| field.ray_march(root, origin, down)),
+        )
+        .await;
+        let mut candidates = fields
+            .iter()
+            .zip(hits)
+            .filter_map(|(field, hit)| match hit {
+                Ok(hit) if hit.min_distance <= 0.0 && hit.deepest_point_distance > 0.0 => {
+                    Some((hit.deepest_point_distance, field))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        for (_, field) in candidates {
+            let Some(surface) = trace_surface(field, root, origin, down, dock.max_distance).await
+            else {
+                continue;
+            };
+            let Ok(normal) = field.normal(root, surface).await else {
+                continue;
+            };
+            let normal = Vec3::from(normal).normalize_or(-down);
+            let rotation = Quat::from_rotation_arc(rotation * Vec3::Y, normal) * rotation;
+            let center = surface + (normal * (size.y / 2.0));
+            _ = spatial.set_relative_transform(
+                root,
+                Transform::from_translation_rotation(center - (rotation * bounds_center), rotation),
+            );
+            return;
+        }
+    }
+    /// fields of other objects whose bounds are within `range` of `selection`, nearest first
+    pub async fn nearby_fields(
+        &mut self,
+        selection: &CapturedSelection,
+        range: f32,
+        limit: usize,
+    ) -> Vec<FieldRef> {
+        let root = self.selection_lines.client().get_root();
+        let Ok(Some(position)) = selection
+            .spatial()
+            .get_transform(root)
+            .await
+            .map(|transform| transform.translation.map(Vec3::from))
+        else {
+            return Vec::new();
+        };
+        let (_, size) = selection.bounds();
+        let own_ids = std::iter::once(selection.id())
+            .chain(selection.members().iter().map(CapturedSelection::id))
+            .cloned()
+            .collect::<HashSet<_>>();
+        self.refresh_objects().await;
+        let objects = self
+            .objects
+            .iter()
+            .filter_map(|(id, (spatial, _, _, field))| {
+                let field = field.clone()?;
+                (!own_ids.contains(id)).then(|| (id.clone(), spatial.clone(), field))
+            })
+            .collect::<Vec<_>>();
+        let bounds = join_all(objects.iter().map(|(id, spatial, _)| {
+            let cached_bounds = self.bounds_cache.get(id);
+            async move {
+                match cached_bounds {
+                    Some(bounds) => (Some(bounds), false),
+                    None => (bounding_sphere(spatial, root).await, true),
+                }
+            }
+        }))
+        .await;
+        let mut nearby = Vec::new();
+        for ((id, _, field), (bounds, fresh)) in objects.into_iter().zip(bounds) {
+            let Some((center, radius)) = bounds else {
+                continue;
+            };
+            if fresh {
+                self.bounds_cache.insert(id, center, radius);
+            }
+            let gap = center.distance(position) - radius - (size.max_element() / 2.0);
+            if gap <= range {
+                nearby.push((gap, field));
+            }
+        }
+        nearby.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        nearby
+            .into_iter()
+            .take(limit)
+            .map(|(_, field)| field)
+            .collect()
+    }
+    /// adds the highlighted object to the group, or removes it if it's already part of it
+    pub fn toggle_add_to_selection(&mut self) {
+        let Some(selection) = self.selection.as_ref() else {
+            return;
+        };
+        let id = selectable_id(selection);
+        if let Some(index) = self
+            .group
+            .iter()
+            .position(|member| selectable_id(member) == id)
+        {
+            self.group.remove(index);
+        } else {
+            self.group.push(selection.clone());
+        }
+    }
+    /// how many objects were added with [`Selector::toggle_add_to_selection`]
+    pub fn group_len(&self) -> usize {
+        self.group.len()
+    }
+    pub fn clear_group(&mut self) {
+        self.group.clear();
+        _ = self.group_lines.set_lines(&[]);
+    }
+    /// captures every object added with [`Selector::toggle_add_to_selection`] so they move as a
+    /// rigid group around the center of their combined bounding box.
+    ///
+    /// the first object that could be captured carries the others, see
+    /// [`CapturedSelection::members`]. objects that can't be captured (e.g. because another
+    /// client holds their lock) are left out, every member unlocks its own object when dropped
+    pub async fn capture_group(&mut self) -> Option<CapturedSelection> {
+        let group = std::mem::take(&mut self.group);
+        _ = self.group_lines.set_lines(&[]);
+        self.capture_together(group).await.captured
+    }
+    /// captures every object whose origin is within `radius` of `center` (relative to
+    /// `ref_space`) as a group, the same way [`Selector::capture_group`] does.
+    ///
+    /// objects that can't be captured are skipped and listed in [`SweepCapture::skipped`]
+    pub async fn capture_within_radius(
+        &mut self,
+        center: Vec3,
+        radius: f32,
+        ref_space: &impl SpatialRefAspect,
+    ) -> SweepCapture {
+        self.refresh_objects().await;
+        let objects = self
+            .objects
+            .iter()
+            .map(|(_, object)| object)
+            .filter(|(spatial, _, _, field)| {
+                self.settings
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter(spatial, field.as_ref()))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let positions = join_all(
+            objects
+                .iter()
+                .map(|(spatial, ..)| spatial.get_transform(ref_space)),
+        )
+        .await;
+        let hits = objects
+            .into_iter()
+            .zip(positions)
+            .filter(|(_, transform)| {
+                transform
+                    .as_ref()
+                    .ok()
+                    .and_then(|transform| transform.translation)
+                    .is_some_and(|position| Vec3::from(position).distance(center) <= radius)
+            })
+            .map(|(object, _)| object)
+            .collect::<Vec<_>>();
+        if hits.is_empty() {
+            return SweepCapture::default();
+        }
+        self.clear_selection();
+        self.capture_together(hits).await
+    }
+    /// the first object that can be captured carries the others, see [`CapturedSelection::members`]
+    async fn capture_together(&mut self, objects: Vec<Selectable>) -> SweepCapture {
+        let mut sweep = SweepCapture::default();
+        let Some((min, max)) = self.bounds_of(&objects).await else {
+            sweep.skipped = objects
+                .iter()
+                .map(|object| (selectable_id(object), CaptureError::BoundsUnavailable))
+                .collect();
+            return sweep;
+        };
+        let center = (min + max) / 2.0;
+        for object in objects {
+            let id = selectable_id(&object);
+            let pivot = sweep.captured.is_none().then_some(center);
+            let member = match self.capture_object(object, pivot).await {
+                Ok(member) => member,
+                Err(err) => {
+                    sweep.skipped.push((id, err));
+                    continue;
+                }
+            };
+            match sweep.captured.as_mut() {
+                None => sweep.captured = Some(member),
+                Some(leader) => {
+                    _ = member.spatial.set_spatial_parent_in_place(&leader.spatial);
+                    leader.members.push(member);
+                }
+            }
+        }
+        if let Some(leader) = sweep.captured.as_mut() {
+            leader.bounds = (Vec3::ZERO, max - min);
+            _ = self.target_model.set_enabled(true);
+            self.place_target_model(&leader.spatial, Vec3::ZERO, max - min);
+        }
+        sweep
+    }
+    /// min and max corner of the box around all `objects`, relative to the client root
+    async fn bounds_of(&self, objects: &[Selectable]) -> Option<(Vec3, Vec3)> {
+        let root = self.group_lines.client().get_root();
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        for (spatial, ..) in objects {
+            let Ok(bb) = spatial.get_relative_bounding_box(root).await else {
+                continue;
+            };
+            let half_size = Vec3::from(bb.size) / 2.0;
+            let (min, max) = (
+                Vec3::from(bb.center) - half_size,
+                Vec3::from(bb.center) + half_size,
+            );
+            bounds = Some(match bounds {
+                Some((bounds_min, bounds_max)) => (bounds_min.min(min), bounds_max.max(max)),
+                None => (min, max),
+            });
+        }
+        bounds
+    }
+    async fn update_group_lines(&self) {
+        if self.group.is_empty() {
+            return;
+        }
+        let root = self.group_lines.client().get_root();
+        let mut lines = Vec::new();
+        for (spatial, ..) in &self.group {
+            let Ok(bb) = spatial.get_relative_bounding_box(root).await else {
+                continue;
+            };
+            lines.extend(bounding_box(bb).into_iter().map(|l| l.thickness(0.0025)));
+        }
+        _ = self.group_lines.set_lines(&lines);
+    }
+    /// sizes the solver target model to hug a box of `size` around `center` relative to `parent`
+    /// and shows `parent`'s axes from `center`
+    fn place_target_model(&self, parent: &impl SpatialRefAspect, center: Vec3, size: Vec3) {
+        // bounds that aren't computed yet can come back as NaN
+        let center = Vec3::select(center.is_nan_mask(), Vec3::ZERO, center);
+        let size = Vec3::select(size.is_nan_mask(), Vec3::ZERO, size).abs();
+        _ = self.axis_lines.set_spatial_parent(parent);
+        _ = self
+            .axis_lines
+            .set_local_transform(Transform::from_translation(center));
+        _ = self.axis_lines.set_lines(&axis_lines(
+            size.max_element()
+                .max(0.05)
+                .min(self.settings.max_target_size)
+                * 0.75,
+        ));
+        let (rotation, scale) = target_pose(
+            size,
+            self.settings.target_fit,
+            self.settings.min_highlight_size,
+            self.settings.max_target_size,
+        );
+        _ = self.target_model.set_spatial_parent(parent);
+        _ = self
+            .target_model
+            .set_local_transform(Transform::from_translation_rotation_scale(
+                center, rotation, scale,
+            ));
+    }
+    /// captures a specific object from the registry, whether it's highlighted or not
+    pub async fn capture(&mut self, id: &ObjectId) -> Result<CapturedSelection, CaptureError> {
+        let object = self
+            .find_object(id)
+            .await
+            .ok_or(CaptureError::NotReparentable)?;
+        self.capture_object(object, None).await
+    }
+    /// whether the object is still in the registry
+    pub async fn contains(&mut self, id: &ObjectId) -> bool {
+        self.find_object(id).await.is_some()
+    }
+    /// drops the selection and group members that left the registry,
+    /// so they aren't highlighted or captured through a stale reference
+    async fn forget_lost_objects(&mut self) {
+        self.refresh_objects().await;
+        if !self.object_lost.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let ids = self
+            .objects
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect::<HashSet<_>>();
+        if self
+            .current_selection()
+            .is_some_and(|selected| !ids.contains(&selected))
+        {
+            self.clear_selection();
+        }
+        self.group
+            .retain(|member| ids.contains(&selectable_id(member)));
+    }
+    async fn find_object(&mut self, id: &ObjectId) -> Option<Selectable> {
+        self.refresh_objects().await;
+        self.objects
+            .iter()
+            .find(|(object_id, _)| object_id == id)
+            .map(|(_, object)| object.clone())
+    }
+    /// takes a new snapshot of the registry's objects if they changed since the last one
+    async fn refresh_objects(&mut self) {
+        let changed = self.objects_changed.swap(false, Ordering::Relaxed);
+        if !changed && !std::mem::take(&mut self.refresh_again) {
+            return;
+        }
+        self.refresh_again = changed;
+        self.objects = self
+            .query
+            .iter()
+            .await
+            .values()
+            .map(|object| (selectable_id(object), object.clone()))
+            .collect();
+    }
+    /// `pivot` places the spatial the object is moved by relative to the client root,
+    /// `None` puts it at the object's origin
+    async fn capture_object(
+        &mut self,
+        (spatial_ref, reparentable, reparent_lock, _): Selectable,
+        pivot: Option<Vec3>,
+    ) -> Result<CapturedSelection, CaptureError> {
+        if self.settings.use_reparent_lock {
+            let lock_started = Instant::now();
+            let unlock = reparent_lock.clone();
+            let locked = lock_within_capture_timeout(
+                self.settings.capture_timeout,
+                acquire_lock(
+                    reparent_lock.clone(),
+                    self.settings.lock_retries,
+                    self.settings.lock_retry_backoff,
+                ),
+                move || async move {
+                    _ = unlock.unlock().await;
                 },
             )
-            .unwrap();
-        _ = reparentable
-            .parent(spatial.export_spatial().await.ok()?)
             .await;
-        _ = self.selection_lines.set_lines(&[]);
-        _ = self.target_model.set_enabled(true);
-        {
-            let bb = spatial_ref.get_local_bounding_box().await.ok()?;
-            let longest = Vec3Component::find_longest(bb.size);
-            let other_size = longest.other_max(bb.size);
-            _ = self.target_model.set_spatial_parent(&spatial_ref);
-            _ = self
-                .target_model
-                .set_local_transform(Transform::from_translation_rotation_scale(
-                    bb.center,
-                    longest.rotation() * Quat::from_rotation_y(f32::consts::FRAC_PI_2),
-                    [other_size * 2.0; 3],
-                ));
+            match locked {
+                Ok(attempt) => {
+                    debug!(attempt, elapsed = ?lock_started.elapsed(), "reparent lock acquired");
+                }
+                Err(CaptureError::TimedOut) => {
+                    debug!(
+                        destination = %reparent_lock.inner().destination(),
+                        path = %reparent_lock.inner().path(),
+                        "timed out waiting for the reparent lock"
+                    );
+                    return Err(CaptureError::TimedOut);
+                }
+                Err(CaptureError::LockHeld) => {
+                    self.lock_contention
+                        .record(self.settings.lock_contention_log_interval);
+                    return Err(CaptureError::LockHeld);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        let root = self.selection_lines.client().get_root();
+        let original_transform = spatial_ref.get_transform(root).await.ok();
+        let captured = self
+            .reparent_to_solver(&spatial_ref, &reparentable, pivot)
+            .await;
+        let (spatial, bounds) = match captured {
+            Ok(captured) => captured,
+            Err(err) => {
+                if self.settings.use_reparent_lock {
+                    _ = reparent_lock.unlock().await;
+                }
+                return Err(err);
+            }
+        };
+        let id = ObjectId::from_proxy(reparentable.inner());
+        let mass = self.mass_of(reparentable.inner()).await;
+        if let Some(transform) = original_transform {
+            if self.undo.len() >= UNDO_DEPTH {
+                self.undo.pop_front();
+            }
+            self.undo.push_back(UndoEntry {
+                id: id.clone(),
+                translation: transform.translation.map(Vec3::from).unwrap_or_default(),
+                rotation: transform.rotation.map(Quat::from).unwrap_or_default(),
+            });
         }
-        Some(CapturedSelection {
+        _ = self.events.send(SolverEvent::Captured(id.clone()));
+        Ok(CapturedSelection {
+            id,
+            events: self.events.clone(),
             spatial,
             reparentable,
             reparent_lock,
+            locked: self.settings.use_reparent_lock,
+            release: self.settings.release,
             target_model: self.target_model.clone(),
+            axis_lines: self.axis_lines.clone(),
+            bounds,
+            members: Vec::new(),
+            snap: None,
+            mass,
+            torn_down: false,
         })
     }
-    pub async fn update_selection(&mut self, ray: Ray) {
-        let mut closest_target = None;
-        for obj @ (spatial, _, _, field) in self.query.iter().await.deref().values() {
-            let distance = if let Some(field) = field {
-                let Ok(raymarch_result) = field
-                    .ray_march(&ray.ref_space, ray.origin, ray.direction)
-                    .await
-                else {
-                    continue;
-                };
-                // field not hit
-                if raymarch_result.min_distance > 0.0 {
-                    continue;
-                }
-                raymarch_result.deepest_point_distance
-            } else {
-                let Ok(Some(pos)) = spatial
-                    .get_transform(&ray.ref_space)
-                    .await
-                    .map(|t| t.translation)
-                else {
-                    continue;
-                };
-                let pos = Vec3::from(pos);
-                let ray_relative = pos - ray.origin;
-                let ray_distance = ray_relative.dot(ray.direction);
-                // spatial is behind ray
-                if ray_distance.is_sign_negative() {
-                    continue;
-                }
-                let point_on_ray = ray.origin + (ray.direction * ray_distance);
-                let distance_from_ray = pos.distance(point_on_ray);
-                // a cone shape to make selecting far away objects easier
-                if distance_from_ray > ray_distance * 0.1 {
-                    continue;
+    /// reads the object's `org.stardustxr.Mass` property, `None` for objects without one
+    async fn mass_of(&self, object: &zbus::Proxy<'static>) -> Option<f32> {
+        let mass = async {
+            MassHintProxy::builder(object.connection())
+                .destination(object.destination().to_owned())?
+                .path(object.path().to_owned())?
+                .cache_properties(zbus::proxy::CacheProperties::No)
+                .build()
+                .await?
+                .mass()
+                .await
+        };
+        match tokio::time::timeout(self.settings.capture_timeout, mass).await {
+            Ok(Ok(mass)) if mass.is_finite() && mass > 0.0 => Some(mass as f32),
+            Ok(Ok(mass)) => {
+                debug!(mass, "ignoring invalid mass hint");
+                None
+            }
+            Ok(Err(_)) | Err(_) => None,
+        }
+    }
+    /// puts the most recently captured object back where it was before that capture.
+    ///
+    /// objects that are gone by now are skipped in favor of the one captured before them.
+    /// the object is handed back to its original parent the same way releasing does, so if
+    /// that parent no longer exists it ends up wherever its client puts unparented objects
+    /// (usually the root). only position and rotation are restored, not scale.
+    /// returns whether an object was moved back, the move stays on the stack when it failed
+    pub async fn undo_last_move(&mut self) -> bool {
+        while let Some(entry) = self.undo.pop_back() {
+            let Some((spatial_ref, reparentable, reparent_lock, _)) =
+                self.find_object(&entry.id).await
+            else {
+                debug!(id = %entry.id, "object to undo the move of is gone");
+                continue;
+            };
+            if self.settings.use_reparent_lock {
+                let (lock, unlock) = (reparent_lock.clone(), reparent_lock.clone());
+                let locked = lock_within_capture_timeout(
+                    self.settings.capture_timeout,
+                    async move { lock.lock().await.map_err(|_| CaptureError::LockHeld) },
+                    move || async move {
+                        _ = unlock.unlock().await;
+                    },
+                )
+                .await;
+                if let Err(err) = locked {
+                    debug!(id = %entry.id, %err, "unable to undo move, no reparent lock");
+                    self.undo.push_back(entry);
+                    return false;
                 }
+            }
+            let moved_back = self.move_back(&entry, &spatial_ref, &reparentable).await;
+            if self.settings.use_reparent_lock {
+                _ = reparent_lock.unlock().await;
+            }
+            if !moved_back {
+                debug!(id = %entry.id, "unable to undo move, reparenting failed");
+                self.undo.push_back(entry);
+            }
+            return moved_back;
+        }
+        false
+    }
+    /// puts the object where `entry` says it was and hands it back to its parent,
+    /// returns whether the object could be reparented to do so
+    async fn move_back(
+        &self,
+        entry: &UndoEntry,
+        spatial_ref: &SpatialRef,
+        reparentable: &ReparentableProxy<'static>,
+    ) -> bool {
+        let root = self.selection_lines.client().get_root();
+        let Ok(spatial) = Spatial::create(root, Transform::none()) else {
+            return false;
+        };
+        _ = spatial.set_relative_transform(
+            spatial_ref,
+            Transform::from_translation_rotation([0.0; 3], Quat::IDENTITY),
+        );
+        let Ok(exported) = spatial.export_spatial().await else {
+            return false;
+        };
+        if reparentable.parent(exported).await.is_err() {
+            return false;
+        }
+        _ = spatial.set_relative_transform(
+            root,
+            Transform::from_translation_rotation(entry.translation, entry.rotation),
+        );
+        _ = reparentable.unparent().await;
+        true
+    }
+    /// parents the object to a new spatial the solver can move around,
+    /// returns that spatial and the object's bounds relative to it
+    async fn reparent_to_solver(
+        &mut self,
+        spatial_ref: &SpatialRef,
+        reparentable: &ReparentableProxy<'static>,
+        pivot: Option<Vec3>,
+    ) -> Result<(Spatial, (Vec3, Vec3)), CaptureError> {
+        let root = self.selection_lines.client().get_root();
+        let spatial =
+            Spatial::create(root, Transform::none()).map_err(|_| CaptureError::ExportFailed)?;
+        match pivot {
+            Some(pivot) => spatial
+                .set_local_transform(Transform::from_translation(pivot))
+                .unwrap(),
+            None => spatial
+                .set_relative_transform(
+                    spatial_ref,
+                    Transform {
+                        translation: Some([0.; 3].into()),
+                        rotation: Some(Quat::IDENTITY.into()),
+                        scale: None,
+                    },
+                )
+                .unwrap(),
+        }
+        let exported = within_capture_timeout(self.settings.capture_timeout, async {
+            spatial
+                .export_spatial()
+                .await
+                .map_err(|_| CaptureError::ExportFailed)
+        })
+        .await?;
+        // the caller unlocks the object when this fails
+        reparentable
+            .parent(exported)
+            .await
+            .map_err(|_| CaptureError::ReparentFailed)?;
+        let Ok(bb) = spatial_ref.get_local_bounding_box().await else {
+            _ = reparentable.unparent().await;
+            return Err(CaptureError::BoundsUnavailable);
+        };
+        self.hide_highlight();
+        _ = self.target_model.set_enabled(true);
+        self.place_target_model(spatial_ref, bb.center.into(), bb.size.into());
+        Ok((spatial, (Vec3::from(bb.center), Vec3::from(bb.size))))
+    }
+    /// highlights the object `ray` points at best
+    pub async fn update_selection(&mut self, ray: Ray) {
+        let span = debug_span!("update_selection", objects = Empty, score = Empty);
+        let started = Instant::now();
+        let evaluated = self.select_along(ray).instrument(span.clone()).await;
+        debug!(parent: &span, evaluated, elapsed = ?started.elapsed(), "selection pass");
+    }
+    /// returns how many objects were evaluated
+    async fn select_along(&mut self, ray: Ray) -> usize {
+        self.forget_lost_objects().await;
+        self.update_group_lines().await;
+        let root = self.selection_lines.client().get_root();
+        // the ray relative to the client root, to check it against cached bounds
+        let root_ray = ray
+            .ref_space
+            .get_transform(root)
+            .await
+            .ok()
+            .map(|transform| {
+                let ref_to_root = Mat4::from_scale_rotation_translation(
+                    transform.scale.map(Vec3::from).unwrap_or(Vec3::ONE),
+                    transform.rotation.map(Quat::from).unwrap_or_default(),
+                    transform.translation.map(Vec3::from).unwrap_or_default(),
+                );
+                (
+                    ref_to_root.transform_point3(ray.origin),
+                    ref_to_root
+                        .transform_vector3(ray.direction)
+                        .normalize_or_zero(),
+                )
+            });
+        let objects: Vec<(ObjectId, Selectable)> = self
+            .objects
+            .iter()
+            .filter(|(id, _)| self.excluded.as_ref() != Some(id))
+            .filter(|(_, (spatial, _, _, field))| {
+                self.settings
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter(spatial, field.as_ref()))
+            })
+            .cloned()
+            .collect();
+        let evaluated = objects.len();
+        Span::current().record("objects", evaluated);
+        let params = &RayParams {
+            origin: ray.origin,
+            direction: ray.direction,
+            root_ray,
+            needs_projection: self.settings.auto_select_single
+                || self.settings.min_selection_distance > 0.0,
+            min_selection_distance: self.settings.min_selection_distance,
+            selection_cone: self.settings.selection_cone,
+            max_range: self.settings.max_range,
+        };
+        // every object's queries are issued at once, waiting on them one by one adds up quickly
+        let results = join_all(objects.iter().map(|(id, (spatial, _, _, field))| {
+            let object = RegistryObject {
+                id,
+                spatial,
+                field: field.as_ref(),
+                ray: &ray,
+                root,
+            };
+            let cached = (self.bounds_cache.get(id), self.bounds_cache.get_box(id));
+            async move { query_object(&object, cached, params).await }
+        }))
+        .await;
 
-                distance_from_ray + ray_distance
+        let mut targets = Vec::new();
+        let mut near_targets = Vec::new();
+        let mut auto_select_candidates = Vec::new();
+        let mut candidates = Vec::new();
+        let mut seen = HashSet::new();
+        for ((id, obj), query) in objects.into_iter().zip(results) {
+            if let Some((center, radius)) = query.fresh_bounds {
+                self.bounds_cache.insert(id.clone(), center, radius);
+            }
+            if let Some((center, size)) = query.fresh_box {
+                self.bounds_cache.insert_box(id.clone(), center, size);
+            }
+            seen.insert(id);
+            if self.settings.auto_select_single
+                && query
+                    .projection
+                    .is_some_and(|(ray_distance, distance_from_ray)| {
+                        ray_distance <= self.settings.max_range
+                            && distance_from_ray <= ray_distance * self.settings.auto_select_cone
+                    })
+            {
+                auto_select_candidates.push(obj.clone());
+            }
+            if let Some((ray_distance, distance_from_ray)) = query.projection
+                && ray_distance < self.settings.min_selection_distance
+            {
+                near_targets.push((ray_distance.hypot(distance_from_ray), obj));
+                continue;
+            }
+            let Some((hit, score)) = query.target(&self.settings.scoring) else {
+                continue;
             };
+            candidates.push((selectable_id(&obj), hit.distance, score));
+            targets.push((score, (obj, query.confidence)));
+        }
+        let closest_target = best_candidate(targets)
+            .map(|(distance, (obj, confidence))| (distance, obj, confidence));
+        let near_target = best_candidate(near_targets);
+        self.bounds_cache.retain(&seen);
+        candidates.sort_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+        self.candidates = candidates;
+        let (mut score, mut closest_target, mut confidence) = closest_target
+            .map(|(distance, target, confidence)| (distance, Some(target), confidence))
+            .unwrap_or((f32::INFINITY, None, None));
+        // with multiple candidates in view we can't know which one is meant
+        if auto_select_candidates.len() == 1 {
+            closest_target = auto_select_candidates.pop();
+            score = f32::INFINITY;
+            confidence = None;
+        }
+        if let Some((distance, target)) = near_target {
+            closest_target = Some(target);
+            score = distance;
+            confidence = None;
+        }
+        // a fresh copy keeps the highlight while the ray still rests on its original
+        if let Some((source, copy)) = self.duplicate.clone() {
             if closest_target
                 .as_ref()
-                .is_none_or(|(dist, _)| distance < *dist)
+                .is_none_or(|target| selectable_id(target) == source)
             {
-                closest_target.replace((distance, obj.clone()));
+                if let Some(copy) = self.find_object(&copy).await {
+                    closest_target = Some(copy);
+                    confidence = None;
+                }
+            } else {
+                self.duplicate = None;
             }
         }
-        let closest_target = closest_target.map(|v| v.1);
-        self.selection = closest_target.clone();
+        if let Some(overlay) = self.debug_overlay.as_mut()
+            && let Some((origin, direction)) = root_ray
+            && direction != Vec3::ZERO
+        {
+            let best = closest_target.as_ref().map(selectable_id);
+            let candidates = self
+                .candidates
+                .iter()
+                .map(|(id, distance, score)| {
+                    (
+                        origin + (direction * *distance),
+                        *score,
+                        Some(id) == best.as_ref(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            overlay.draw(root, direction, &candidates);
+        }
+        if closest_target.is_some() {
+            self.last_hit = Some(Instant::now());
+        } else if self.selection.is_some()
+            && self
+                .last_hit
+                .is_some_and(|last_hit| last_hit.elapsed() < self.settings.selection_grace)
+        {
+            return evaluated;
+        }
+        self.set_selection(closest_target.clone());
+        self.selection_score = score;
+        Span::current().record("score", score);
+        self.selection_confidence = confidence;
         let Some(closest_target) = closest_target else {
-            _ = self.selection_lines.set_lines(&[]);
-            return;
+            if self.highlighted.is_some() {
+                self.hide_highlight();
+            }
+            return evaluated;
         };
-        _ = self.selection_lines.set_relative_transform(
-            &closest_target.0,
-            Transform {
-                translation: Some(Vec3::ZERO.into()),
-                rotation: Some(Quat::IDENTITY.into()),
-                scale: None,
-            },
-        );
-        let Ok(bb) = closest_target
+        let id = selectable_id(&closest_target);
+        // parented to the object the highlight follows it around without updating it every frame
+        if self
+            .highlighted
+            .as_ref()
+            .is_none_or(|highlighted| highlighted.id != id)
+        {
+            _ = self.selection_lines.set_spatial_parent(&closest_target.0);
+            _ = self
+                .selection_lines
+                .set_local_transform(Transform::from_translation_rotation(
+                    Vec3::ZERO,
+                    Quat::IDENTITY,
+                ));
+        }
+        let Ok(mut bb) = closest_target
             .0
             .get_relative_bounding_box(&self.selection_lines)
             .await
         else {
             warn!("can't get bounding box");
-            _ = self.selection_lines.set_lines(&[]);
-            return;
+            self.hide_highlight();
+            return evaluated;
+        };
+        bb.size = Vec3::from(bb.size)
+            .max(Vec3::splat(self.settings.min_highlight_size))
+            .into();
+        let highlight = Highlight {
+            id,
+            center: bb.center.into(),
+            size: bb.size.into(),
+            color: self.highlight_color(),
         };
+        if self
+            .highlighted
+            .as_ref()
+            .is_some_and(|highlighted| highlighted.matches(&highlight))
+        {
+            return evaluated;
+        }
         let mut lines = bounding_box(bb);
         lines
             .iter_mut()
-            .for_each(|l| *l = l.clone().thickness(0.0025));
+            .for_each(|l| *l = l.clone().thickness(0.0025).color(highlight.color));
         _ = self.selection_lines.set_lines(&lines);
+        self.highlighted = Some(highlight);
+        evaluated
+    }
+    fn highlight_color(&self) -> Color {
+        let white = rgba_linear!(1.0, 1.0, 1.0, 1.0);
+        if !self.selection_armed() {
+            return rgba_linear!(1.0, 1.0, 1.0, 0.3);
+        }
+        match self.settings.highlight {
+            HighlightStyle::Plain => white,
+            // yellow at the edge of the cone to green right on the ray
+            HighlightStyle::Confidence => self
+                .selection_confidence
+                .map(|confidence| rgba_linear!(1.0 - confidence, 1.0, 0.0, 1.0))
+                .unwrap_or(white),
+            HighlightStyle::Accent => self.accent_color.unwrap_or(white),
+        }
+    }
+    /// the color used with [`HighlightStyle::Accent`]
+    pub fn set_accent_color(&mut self, color: Color) {
+        self.accent_color = Some(color);
+    }
+}
+
+/// the candidate with the lowest score, the first one of those scoring the same.
+/// candidates are scored concurrently but come in the registry's order,
+/// so the pick doesn't depend on which object answered first
+fn best_candidate<T>(candidates: impl IntoIterator<Item = (f32, T)>) -> Option<(f32, T)> {
+    candidates
+        .into_iter()
+        .fold(None::<(f32, T)>, |best, (score, candidate)| {
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score < *best_score)
+            {
+                Some((score, candidate))
+            } else {
+                best
+            }
+        })
+}
+
+fn selectable_id((_, reparentable, _, _): &Selectable) -> ObjectId {
+    ObjectId::from_proxy(reparentable.inner())
+}
+
+/// makes sure two selectors (e.g. one per hand) never highlight the same object.
+///
+/// call this after both selectors ran [`Selector::update_selection`] for the frame.
+/// when both highlight the same object the selector with the better (lower) score keeps it,
+/// on a tie (or a score that can't be compared) `a` keeps it, so the same frame always resolves
+/// the same way. the other selector skips that object until the winner stops highlighting it,
+/// which keeps the highlight from flickering between hands.
+/// the solver drives a single selector for both hands and doesn't need this
+pub fn resolve_shared_highlight(a: &mut Selector, b: &mut Selector) {
+    let a_selected = a.current_selection();
+    let b_selected = b.current_selection();
+    if a.excluded.is_some() && a.excluded != b_selected {
+        a.excluded = None;
+    }
+    if b.excluded.is_some() && b.excluded != a_selected {
+        b.excluded = None;
+    }
+    let Some(shared) = a_selected.filter(|a_selected| Some(a_selected) == b_selected.as_ref())
+    else {
+        return;
+    };
+    let loser = if first_keeps_highlight(a.selection_score, b.selection_score) {
+        b
+    } else {
+        a
+    };
+    loser.clear_selection();
+    loser.excluded = Some(shared);
+}
+
+/// whether the selector that scored `a` keeps a highlight shared with the one that scored `b`
+fn first_keeps_highlight(a: f32, b: f32) -> bool {
+    b.partial_cmp(&a) != Some(std::cmp::Ordering::Less)
+}
+
+/// the selectable objects of an object registry, kept up to date by a mapper task
+struct RegistryQuery {
+    query: ObjectListQuery<Selectable>,
+    // set by the mapper task when an object left the registry
+    object_lost: Arc<AtomicBool>,
+    // set by the mapper task on every change to the matches
+    objects_changed: Arc<AtomicBool>,
+    mapper_task: AbortOnDrop,
+    // warns when the registry stays silent for too long, see [`SelectorSettings::registry_timeout`]
+    watchdog: Option<AbortOnDrop>,
+}
+impl RegistryQuery {
+    fn new(
+        object_registry: Arc<ObjectRegistry>,
+        client: Arc<ClientHandle>,
+        registry_timeout: Option<Duration>,
+    ) -> Self {
+        let (query, mapper) =
+            ObjectQuery::<Selectable, ClientHandle>::new(object_registry, client).to_list_query();
+        let registry_ready = Arc::new(Notify::new());
+        let object_lost = Arc::new(AtomicBool::new(false));
+        let objects_changed = Arc::new(AtomicBool::new(true));
+        let mapper = tokio::spawn(mapper.init({
+            let registry_ready = registry_ready.clone();
+            let object_lost = object_lost.clone();
+            let objects_changed = objects_changed.clone();
+            async move |e| {
+                registry_ready.notify_one();
+                objects_changed.store(true, Ordering::Relaxed);
+                match e {
+                    ListEvent::NewMatch(v) => Some(v),
+                    ListEvent::Modified(v) => Some(v),
+                    // the selection might be gone, checked on the next update
+                    ListEvent::MatchLost => {
+                        object_lost.store(true, Ordering::Relaxed);
+                        None
+                    }
+                    // anything else doesn't describe an object we could select
+                    _ => None,
+                }
+            }
+        }));
+        let watchdog = registry_timeout.map(|timeout| {
+            let task = tokio::spawn(async move {
+                if tokio::time::timeout(timeout, registry_ready.notified())
+                    .await
+                    .is_err()
+                {
+                    warn!(
+                        ?timeout,
+                        "object registry hasn't reported any objects, is it reachable?"
+                    );
+                }
+            });
+            AbortOnDrop(task.abort_handle())
+        });
+        RegistryQuery {
+            query,
+            object_lost,
+            objects_changed,
+            mapper_task: AbortOnDrop(mapper.abort_handle()),
+            watchdog,
+        }
+    }
+}
+
+/// see [`SelectorSettings::debug_scores`]
+struct DebugOverlay {
+    lines: Lines,
+    // reused across frames, the ones beyond the current candidates are left empty
+    labels: Vec<Text>,
+}
+impl DebugOverlay {
+    /// `candidates` are the points relative to the client root where the ray hit each candidate,
+    /// their score and whether they won
+    fn draw(
+        &mut self,
+        root: &impl SpatialRefAspect,
+        ray_direction: Vec3,
+        candidates: &[(Vec3, f32, bool)],
+    ) {
+        let marker = |center: Vec3, color: Color| {
+            [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| Line {
+                points: [center - (axis * 0.01), center + (axis * 0.01)]
+                    .into_iter()
+                    .map(|point| LinePoint {
+                        point: point.into(),
+                        thickness: 0.001,
+                        color,
+                    })
+                    .collect(),
+                cyclic: false,
+            })
+        };
+        let lines = candidates
+            .iter()
+            .flat_map(|(point, _, best)| {
+                let color = if *best {
+                    rgba_linear!(0.0, 1.0, 0.0, 1.0)
+                } else {
+                    rgba_linear!(1.0, 0.5, 0.0, 1.0)
+                };
+                marker(*point, color)
+            })
+            .collect::<Vec<_>>();
+        _ = self.lines.set_lines(&lines);
+        // facing back along the ray towards whoever is aiming
+        let rotation = Quat::from_rotation_arc(Vec3::Z, -ray_direction);
+        while self.labels.len() < candidates.len() {
+            let Ok(label) = Text::create(
+                root,
+                Transform::none(),
+                "",
+                TextStyle {
+                    character_height: 0.01,
+                    ..Default::default()
+                },
+            ) else {
+                break;
+            };
+            self.labels.push(label);
+        }
+        for (i, label) in self.labels.iter().enumerate() {
+            let Some((point, score, _)) = candidates.get(i) else {
+                _ = label.set_text("");
+                continue;
+            };
+            _ = label.set_local_transform(Transform::from_translation_rotation(
+                *point + (Vec3::Y * 0.015),
+                rotation,
+            ));
+            _ = label.set_text(&format!("{score:.3}"));
+        }
+    }
+}
+
+/// bounding spheres of registry objects relative to the client root,
+/// and local bounding boxes of the ones without a field
+#[derive(Debug, Default)]
+struct BoundsCache {
+    entries: HashMap<ObjectId, (Vec3, f32, Instant)>,
+    boxes: HashMap<ObjectId, (Vec3, Vec3, Instant)>,
+}
+impl BoundsCache {
+    /// center and radius, `None` once the cached sphere is too old
+    fn get(&self, id: &ObjectId) -> Option<(Vec3, f32)> {
+        self.entries
+            .get(id)
+            .filter(|(_, _, sampled)| sampled.elapsed() < BOUNDS_CACHE_LIFETIME)
+            .map(|(center, radius, _)| (*center, *radius))
+    }
+    fn insert(&mut self, id: ObjectId, center: Vec3, radius: f32) {
+        self.entries.insert(id, (center, radius, Instant::now()));
+    }
+    /// center and size, `None` once the cached box is too old
+    fn get_box(&self, id: &ObjectId) -> Option<(Vec3, Vec3)> {
+        self.boxes
+            .get(id)
+            .filter(|(_, _, sampled)| sampled.elapsed() < BOUNDS_CACHE_LIFETIME)
+            .map(|(center, size, _)| (*center, *size))
+    }
+    fn insert_box(&mut self, id: ObjectId, center: Vec3, size: Vec3) {
+        self.boxes.insert(id, (center, size, Instant::now()));
+    }
+    /// forgets objects that aren't in the registry anymore
+    fn retain(&mut self, ids: &HashSet<ObjectId>) {
+        self.entries.retain(|id, _| ids.contains(id));
+        self.boxes.retain(|id, _| ids.contains(id));
+    }
+}
+
+/// center and radius of the sphere around a spatial's bounding box relative to `root`
+async fn bounding_sphere(
+    spatial: &SpatialRef,
+    root: &impl SpatialRefAspect,
+) -> Option<(Vec3, f32)> {
+    let bb = spatial.get_relative_bounding_box(root).await.ok()?;
+    Some((Vec3::from(bb.center), Vec3::from(bb.size).length() / 2.0))
+}
+
+/// how a candidate lines up with the ray, see [`SelectionScoring`]
+#[derive(Debug, Clone, Copy)]
+struct RayHit {
+    // along the ray to its deepest point inside the field, to where it enters the bounding box,
+    // or to the object's origin without field or bounds
+    distance: f32,
+    // of the object's center from the ray, or its origin without field or bounds
+    off_axis: f32,
+}
+
+/// the highlight drawn by [`Selector::update_selection`]
+struct Highlight {
+    id: ObjectId,
+    center: Vec3,
+    size: Vec3,
+    color: Color,
+}
+impl Highlight {
+    /// whether drawing `other` would look the same, ignoring bounding box changes too
+    /// small to see
+    fn matches(&self, other: &Highlight) -> bool {
+        const EPSILON: f32 = 0.0005;
+        self.id == other.id
+            && self.center.abs_diff_eq(other.center, EPSILON)
+            && self.size.abs_diff_eq(other.size, EPSILON)
+            && self.color == other.color
+    }
+}
+
+/// where an object was relative to the client root right before it got captured.
+///
+/// its original parent isn't part of the entry: the reparentable interface doesn't tell us
+/// what an object's parent is, and `unparent` hands the object back to that parent by itself,
+/// see [`Selector::undo_last_move`]
+struct UndoEntry {
+    id: ObjectId,
+    translation: Vec3,
+    rotation: Quat,
+}
+
+/// what the queries for a single object in [`Selector::update_selection`] came up with
+struct RayQuery {
+    // distance along and from the ray
+    projection: Option<(f32, f32)>,
+    // where the ray hit the object, if it did
+    hit: Option<RayHit>,
+    // 0..=1, how close the ray passes to the object's center
+    confidence: Option<f32>,
+    // bounding sphere that had to be queried because the cached one was missing or too old
+    fresh_bounds: Option<(Vec3, f32)>,
+    // same for the local bounding box of objects without a field
+    fresh_box: Option<(Vec3, Vec3)>,
+}
+impl RayQuery {
+    /// where the ray hit the object and the score it ranks by among the other objects it hit
+    fn target(&self, scoring: &SelectionScoring) -> Option<(RayHit, f32)> {
+        self.hit.map(|hit| (hit, scoring.score(hit)))
+    }
+}
+
+/// the ray and the settings [`query_object`] goes by
+#[derive(Debug, Clone, Copy)]
+struct RayParams {
+    // the ray in its own space
+    origin: Vec3,
+    direction: Vec3,
+    // the ray relative to the client root, to check it against cached bounds
+    root_ray: Option<(Vec3, Vec3)>,
+    // whether the distance along and from the ray is needed even for objects with a field
+    needs_projection: bool,
+    min_selection_distance: f32,
+    selection_cone: f32,
+    max_range: f32,
+}
+
+/// what [`query_object`] asks the server about an object,
+/// so the scoring can run on made up answers in tests
+trait ObjectQueries {
+    fn has_field(&self) -> bool;
+    /// the object's transform relative to the ray's space
+    async fn transform_to_ray(&self) -> Option<Mat4>;
+    /// center and radius of the object's bounding sphere relative to the client root
+    async fn bounding_sphere(&self) -> Option<(Vec3, f32)>;
+    /// center and size of the object's local bounding box
+    async fn local_box(&self) -> Option<(Vec3, Vec3)>;
+    /// minimum distance and deepest point distance of ray marching the object's field
+    async fn ray_march(&self) -> Option<(f32, f32)>;
+}
+
+/// an object of the registry, queried along the ray [`Selector::select_along`] selects with
+struct RegistryObject<'a, R> {
+    id: &'a ObjectId,
+    spatial: &'a SpatialRef,
+    field: Option<&'a FieldRef>,
+    ray: &'a Ray,
+    root: &'a R,
+}
+impl<R: SpatialRefAspect> ObjectQueries for RegistryObject<'_, R> {
+    fn has_field(&self) -> bool {
+        self.field.is_some()
+    }
+    async fn transform_to_ray(&self) -> Option<Mat4> {
+        self.spatial
+            .get_transform(&self.ray.ref_space)
+            .await
+            .ok()
+            .map(|transform| to_mat4(&transform))
+    }
+    async fn bounding_sphere(&self) -> Option<(Vec3, f32)> {
+        bounding_sphere(self.spatial, self.root).await
+    }
+    async fn local_box(&self) -> Option<(Vec3, Vec3)> {
+        self.spatial
+            .get_local_bounding_box()
+            .await
+            .ok()
+            .map(|bb| (Vec3::from(bb.center), Vec3::from(bb.size)))
+    }
+    async fn ray_march(&self) -> Option<(f32, f32)> {
+        let result = self
+            .field?
+            .ray_march(&self.ray.ref_space, self.ray.origin, self.ray.direction)
+            .instrument(debug_span!("ray_march", id = %self.id))
+            .await
+            .ok()?;
+        Some((result.min_distance, result.deepest_point_distance))
+    }
+}
+
+/// the queries [`Selector::select_along`] makes for a single object and what they came up with,
+/// `cached` are the object's bounding sphere and local box if the [`BoundsCache`] still has them
+async fn query_object(
+    object: &impl ObjectQueries,
+    (cached_bounds, cached_box): (Option<(Vec3, f32)>, Option<(Vec3, Vec3)>),
+    params: &RayParams,
+) -> RayQuery {
+    let RayParams {
+        origin: ray_origin,
+        direction: ray_direction,
+        root_ray,
+        needs_projection,
+        min_selection_distance,
+        selection_cone,
+        max_range,
+    } = *params;
+    let has_field = object.has_field();
+    // without a field the transform is needed for both the projection and the box,
+    // so it's only fetched once
+    let local_to_ray = if !has_field || needs_projection {
+        object.transform_to_ray().await
+    } else {
+        None
+    };
+    let projection = local_to_ray.and_then(|local_to_ray| {
+        project_onto_ray(
+            local_to_ray.transform_point3(Vec3::ZERO),
+            ray_origin,
+            ray_direction,
+        )
+    });
+    let mut query = RayQuery {
+        projection,
+        hit: None,
+        confidence: None,
+        fresh_bounds: None,
+        fresh_box: None,
+    };
+    if projection.is_some_and(|(ray_distance, _)| ray_distance < min_selection_distance) {
+        return query;
+    }
+    query.hit = if has_field {
+        let bounds = match cached_bounds {
+            Some(bounds) => Some(bounds),
+            None => {
+                query.fresh_bounds = object.bounding_sphere().await;
+                query.fresh_bounds
+            }
+        };
+        // broad phase, ray marching every field each frame is expensive
+        if let Some((origin, direction)) = root_ray
+            && let Some((center, radius)) = bounds
+            && (!sphere_near_ray(center, radius + BROAD_PHASE_MARGIN, origin, direction)
+                || (center - origin).dot(direction) - radius > max_range)
+        {
+            None
+        } else {
+            let distance = match object.ray_march().await {
+                // field not hit
+                Some((min_distance, _)) if min_distance > 0.0 => None,
+                Some((_, deepest_point_distance)) if deepest_point_distance > max_range => None,
+                Some((_, deepest_point_distance)) => Some(deepest_point_distance),
+                None => None,
+            };
+            // how close the ray passes to the center of the object's bounds
+            let mut off_axis = 0.0;
+            if distance.is_some()
+                && let Some((origin, direction)) = root_ray
+                && let Some((center, radius)) = bounds
+                && radius > 0.0
+            {
+                let along = (center - origin).dot(direction).max(0.0);
+                off_axis = center.distance(origin + (direction * along));
+                query.confidence = Some(1.0 - (off_axis / radius).clamp(0.0, 1.0));
+            }
+            distance.map(|distance| RayHit { distance, off_axis })
+        }
+    } else if let Some(local_to_ray) = local_to_ray
+        && let Some((center, size)) = match cached_box {
+            Some(local_box) => Some(local_box),
+            None => {
+                query.fresh_box = object.local_box().await;
+                query.fresh_box
+            }
+        }
+        && let Some(box_hit) = intersect_box(center, size, local_to_ray, ray_origin, ray_direction)
+    {
+        // large objects can be aimed at anywhere on them, not just at their origin
+        match box_hit {
+            Some((hit, _)) if hit.distance > max_range => None,
+            Some((hit, centered)) => {
+                query.confidence = Some(centered);
+                Some(hit)
+            }
+            None => None,
+        }
+    } else {
+        // without bounds all there is to go by is the object's origin
+        match projection {
+            Some((ray_distance, _)) if ray_distance > max_range => None,
+            // a cone shape to make selecting far away objects easier
+            Some((ray_distance, distance_from_ray))
+                if distance_from_ray > ray_distance * selection_cone =>
+            {
+                None
+            }
+            // a tighter cone also penalizes being off axis more
+            Some((ray_distance, distance_from_ray)) => {
+                query.confidence = Some(if ray_distance > 0.0 {
+                    1.0 - (distance_from_ray / (ray_distance * selection_cone)).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                });
+                Some(RayHit {
+                    distance: ray_distance,
+                    off_axis: distance_from_ray * (DEFAULT_SELECTION_CONE / selection_cone),
+                })
+            }
+            None => None,
+        }
+    };
+    query
+}
+
+/// rotation and scale of the target model around bounds of `size`, each axis of the target is
+/// kept between `min_size` and `max_size`, see [`SelectorSettings::target_fit`]
+fn target_pose(size: Vec3, fit: TargetFit, min_size: f32, max_size: f32) -> (Quat, Vec3) {
+    let clamp = |size: f32| size.max(min_size).min(max_size);
+    let longest = Vec3Component::find_longest(size);
+    let rotation = longest.rotation() * Quat::from_rotation_y(f32::consts::FRAC_PI_2);
+    let scale = match fit {
+        TargetFit::Uniform => Vec3::splat(clamp(longest.other_max(size))),
+        // the size of the bounds along each of the model's own axes
+        TargetFit::PerAxis => Vec3::from(
+            Vec3Component::ALL
+                .map(|axis| (rotation * axis.unit()).abs().dot(size))
+                .map(clamp),
+        ),
+    };
+    (rotation, scale * 2.0)
+}
+
+/// [`CaptureError::TimedOut`] once `step` took longer than `timeout`,
+/// see [`SelectorSettings::capture_timeout`]
+async fn within_capture_timeout<T>(
+    timeout: Duration,
+    step: impl Future<Output = Result<T, CaptureError>>,
+) -> Result<T, CaptureError> {
+    tokio::time::timeout(timeout, step)
+        .await
+        .unwrap_or(Err(CaptureError::TimedOut))
+}
+
+/// [`within_capture_timeout`] for taking a lock. a `lock` that is still in flight when the
+/// timeout hits keeps going in the background, and `unlock` releases the lock only if it's
+/// granted after all. a lock we never got, e.g. because another client holds it, is left alone
+async fn lock_within_capture_timeout<T, U>(
+    timeout: Duration,
+    lock: impl Future<Output = Result<T, CaptureError>> + Send + 'static,
+    unlock: impl FnOnce() -> U + Send + 'static,
+) -> Result<T, CaptureError>
+where
+    T: Send + 'static,
+    U: Future + Send,
+{
+    let mut lock = Box::pin(lock);
+    match tokio::time::timeout(timeout, &mut lock).await {
+        Ok(locked) => locked,
+        Err(_) => {
+            tokio::spawn(async move {
+                if lock.await.is_ok() {
+                    unlock().await;
+                }
+            });
+            Err(CaptureError::TimedOut)
+        }
+    }
+}
+
+/// takes the object's reparent lock, retrying with a growing backoff while another client
+/// holds it, returns how many retries it took
+async fn acquire_lock(
+    reparent_lock: ReparentLockProxy<'static>,
+    retries: u32,
+    backoff: Duration,
+) -> Result<u32, CaptureError> {
+    let mut attempt = 0;
+    while let Err(err) = reparent_lock.lock().await {
+        if attempt >= retries {
+            debug!(
+                destination = %reparent_lock.inner().destination(),
+                path = %reparent_lock.inner().path(),
+                %err,
+                "reparent lock contended"
+            );
+            return Err(CaptureError::LockHeld);
+        }
+        attempt += 1;
+        tokio::time::sleep(backoff * attempt).await;
     }
+    Ok(attempt)
+}
+
+/// whether a sphere could intersect a ray, `direction` has to be normalized
+fn sphere_near_ray(center: Vec3, radius: f32, origin: Vec3, direction: Vec3) -> bool {
+    let ray_distance = (center - origin).dot(direction);
+    if ray_distance < -radius {
+        return false;
+    }
+    let closest = origin + (direction * ray_distance.max(0.0));
+    closest.distance(center) <= radius
+}
+
+/// counts captures that failed because the reparent lock was held by someone else
+#[derive(Debug, Default)]
+struct LockContention {
+    count: u32,
+    last_log: Option<Instant>,
+}
+impl LockContention {
+    fn record(&mut self, log_interval: Option<Duration>) {
+        self.count += 1;
+        let Some(log_interval) = log_interval else {
+            return;
+        };
+        let now = Instant::now();
+        if self
+            .last_log
+            .is_some_and(|last_log| now.duration_since(last_log) < log_interval)
+        {
+            return;
+        }
+        info!(
+            count = self.count,
+            "captures failed due to reparent lock contention since last report"
+        );
+        self.count = 0;
+        self.last_log = Some(now);
+    }
+}
+
+/// identifies an object from the object registry across frames
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    pub(crate) destination: String,
+    pub(crate) path: String,
+}
+impl ObjectId {
+    fn from_proxy(proxy: &Proxy) -> Self {
+        Self {
+            destination: proxy.destination().to_string(),
+            path: proxy.path().to_string(),
+        }
+    }
+}
+impl std::fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.destination, self.path)
+    }
+}
+
+/// the outcome of [`Selector::capture_within_radius`]
+#[derive(Debug, Default)]
+pub struct SweepCapture {
+    /// every object that could be captured, moving together, `None` when none could be
+    pub captured: Option<CapturedSelection>,
+    /// the objects that were in range but couldn't be captured and why
+    pub skipped: Vec<(ObjectId, CaptureError)>,
 }
 
 #[derive(Debug, Clone)]
 pub struct CapturedSelection {
+    id: ObjectId,
+    events: broadcast::Sender<SolverEvent>,
     spatial: Spatial,
     target_model: Model,
+    axis_lines: Lines,
     reparentable: ReparentableProxy<'static>,
     reparent_lock: ReparentLockProxy<'static>,
+    locked: bool,
+    release: ReleaseSettings,
+    // center and size of the object's bounding box, relative to `spatial`
+    bounds: (Vec3, Vec3),
+    // the rest of a group captured with `Selector::capture_group`, parented to `spatial`
+    members: Vec<CapturedSelection>,
+    snap: Option<SnapSettings>,
+    mass: Option<f32>,
+    // set once the release work was handed off, so dropping doesn't do it twice
+    torn_down: bool,
 }
 
 impl CapturedSelection {
+    /// center and size of the object's bounding box relative to [`CapturedSelection::spatial`]
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        self.bounds
+    }
+    pub fn release_settings(&self) -> &ReleaseSettings {
+        &self.release
+    }
+    /// snapping applied once the selection is let go, see [`Mover::set_snap`](crate::mover::Mover::set_snap).
+    /// snapping while held is up to the mover, so this applies whether or not
+    /// [`SnapSettings::on_release_only`] is set
+    pub fn set_snap(&mut self, snap: Option<SnapSettings>) {
+        self.snap = snap;
+    }
+    pub fn id(&self) -> &ObjectId {
+        &self.id
+    }
+    /// the other objects of a group moving along with this one
+    pub fn members(&self) -> &[CapturedSelection] {
+        &self.members
+    }
+    /// the object's mass hint in kg, used to pick how heavy it feels while moving it.
+    ///
+    /// read once on capture from the `mass` property of the object's `org.stardustxr.Mass`
+    /// interface, see [`MoverMotion::with_mass`](crate::mover::MoverMotion::with_mass).
+    /// objects without it move with the configured motion as is
+    pub fn mass(&self) -> Option<f32> {
+        self.mass
+    }
     pub fn spatial(&self) -> &Spatial {
         &self.spatial
     }
+    /// hands the object back and waits until it's done.
+    ///
+    /// dropping the selection does the same in a background task, which doesn't get to finish
+    /// when the runtime shuts down right after, so use this before exiting
+    pub async fn release(mut self) {
+        self.teardown().await;
+    }
+    /// hides the target model right away and returns the work that hands the object back,
+    /// after this dropping the selection does nothing
+    fn teardown(&mut self) -> impl Future<Output = ()> + Send + 'static {
+        self.torn_down = true;
+        let members = std::mem::take(&mut self.members)
+            .iter_mut()
+            .map(CapturedSelection::teardown)
+            .collect::<Vec<_>>();
+        _ = self.target_model.set_enabled(false);
+        _ = self
+            .target_model
+            .set_spatial_parent(self.spatial.client().get_root());
+        _ = self.axis_lines.set_lines(&[]);
+        _ = self
+            .axis_lines
+            .set_spatial_parent(self.spatial.client().get_root());
+        let id = self.id.clone();
+        let events = self.events.clone();
+        let spatial = self.spatial.clone();
+        let reparentable = self.reparentable.clone();
+        let reparent_lock = self.reparent_lock.clone();
+        let locked = self.locked;
+        let snap = self.snap;
+        async move {
+            // members are parented to our spatial, so they have to let go before it's gone
+            join_all(members).await;
+            if let Some(snap) = &snap {
+                let root = spatial.client().get_root();
+                if let Ok(transform) = spatial.get_transform(root).await {
+                    let (translation, rotation) = snap.snap(
+                        transform.translation.map(Vec3::from).unwrap_or_default(),
+                        transform.rotation.map(Quat::from).unwrap_or_default(),
+                    );
+                    _ = spatial.set_relative_transform(
+                        root,
+                        Transform::from_translation_rotation(translation, rotation),
+                    );
+                }
+            }
+            _ = reparentable.unparent().await;
+            if locked {
+                _ = reparent_lock.unlock().await;
+            }
+            _ = events.send(SolverEvent::Released(id));
+        }
+    }
 }
 
 impl Drop for CapturedSelection {
     fn drop(&mut self) {
-        _ = self.target_model.set_enabled(false);
-        _ = self
-            .target_model
-            .set_spatial_parent(self.spatial().client().get_root());
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                _ = self.reparentable.unparent().await;
-                _ = self.reparent_lock.unlock().await;
-            });
-        });
+        if self.torn_down {
+            return;
+        }
+        // the D-Bus round trips would stall the frame loop, so they run on their own.
+        // this is only the fallback, see `CapturedSelection::release`
+        tokio::spawn(self.teardown());
+    }
+}
+
+/// red X, green Y (up) and blue -Z (forward) lines of `length` from the origin
+fn axis_lines(length: f32) -> [Line; 3] {
+    let axis = |direction: Vec3, color: Color| Line {
+        points: [Vec3::ZERO, direction * length]
+            .into_iter()
+            .map(|point| LinePoint {
+                point: point.into(),
+                thickness: 0.002,
+                color,
+            })
+            .collect(),
+        cyclic: false,
+    };
+    [
+        axis(Vec3::X, rgba_linear!(1.0, 0.0, 0.0, 1.0)),
+        axis(Vec3::Y, rgba_linear!(0.0, 1.0, 0.0, 1.0)),
+        axis(Vec3::NEG_Z, rgba_linear!(0.0, 0.0, 1.0, 1.0)),
+    ]
+}
+
+/// rounds each component to the nearest multiple of `step`
+pub fn snap_to_grid(v: Vec3, step: f32) -> Vec3 {
+    (v / step).round() * step
+}
+
+/// intersects the ray with a local bounding box, `None` if the box is empty.
+/// otherwise where the ray hits the box and how centered on the box
+/// the ray is (0..=1), if it hits the box at all
+fn intersect_box(
+    center: Vec3,
+    size: Vec3,
+    local_to_ray: Mat4,
+    ray_origin: Vec3,
+    ray_direction: Vec3,
+) -> Option<Option<(RayHit, f32)>> {
+    if size.min_element() <= 0.0 {
+        return None;
+    }
+    let ray_to_local = local_to_ray.inverse();
+    let origin = ray_to_local.transform_point3(ray_origin);
+    let direction = ray_to_local.transform_vector3(ray_direction);
+    // slab test
+    let inverse_direction = direction.recip();
+    let t_min = (center - (size / 2.0) - origin) * inverse_direction;
+    let t_max = (center + (size / 2.0) - origin) * inverse_direction;
+    let enter = t_min.min(t_max).max_element().max(0.0);
+    let exit = t_min.max(t_max).min_element();
+    if exit < enter {
+        return Some(None);
+    }
+    let entry_point = local_to_ray.transform_point3(origin + (direction * enter));
+    let along = (center - origin).dot(direction) / direction.length_squared();
+    let closest = origin + (direction * along.max(0.0));
+    let centered = 1.0 - (center.distance(closest) / (size.length() / 2.0)).clamp(0.0, 1.0);
+    Some(Some((
+        RayHit {
+            distance: entry_point.distance(ray_origin),
+            off_axis: local_to_ray
+                .transform_point3(center)
+                .distance(local_to_ray.transform_point3(closest)),
+        },
+        centered,
+    )))
+}
+
+/// steps from `origin` along `direction` until it reaches the field's surface,
+/// `None` if that's further than `max_distance` away
+pub(crate) async fn trace_surface(
+    field: &FieldRef,
+    space: &impl SpatialRefAspect,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<Vec3> {
+    const SURFACE_EPSILON: f32 = 0.001;
+    const MAX_STEPS: usize = 32;
+    let mut traveled = 0.0;
+    for _ in 0..MAX_STEPS {
+        let point = origin + (direction * traveled);
+        let distance = field.distance(space, point).await.ok()?;
+        if distance < SURFACE_EPSILON {
+            return Some(point);
+        }
+        traveled += distance;
+        if traveled > max_distance {
+            return None;
+        }
+    }
+    None
+}
+
+/// returns the distance along the ray and the distance from the ray of a position in the ray's
+/// space, or `None` if it's behind the ray
+fn project_onto_ray(pos: Vec3, origin: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+    let ray_relative = pos - origin;
+    let ray_distance = ray_relative.dot(direction);
+    // spatial is behind ray
+    if ray_distance.is_sign_negative() {
+        return None;
     }
+    let point_on_ray = origin + (direction * ray_distance);
+    Some((ray_distance, pos.distance(point_on_ray)))
 }
 
 #[derive(Debug, Clone)]
@@ -225,12 +2286,21 @@ pub struct Ray {
     pub ref_space: SpatialRef,
 }
 
-enum Vec3Component {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Vec3Component {
     X,
     Y,
     Z,
 }
 impl Vec3Component {
+    pub(crate) const ALL: [Self; 3] = [Self::X, Self::Y, Self::Z];
+    pub(crate) fn unit(&self) -> Vec3 {
+        match self {
+            Vec3Component::X => Vec3::X,
+            Vec3Component::Y => Vec3::Y,
+            Vec3Component::Z => Vec3::Z,
+        }
+    }
     fn find_longest(vec: impl Into<Vec3>) -> Self {
         let v = vec.into();
         if v.x >= v.y && v.x >= v.z {
@@ -266,7 +2336,7 @@ impl Vec3Component {
             Vec3Component::Z => Quat::from_rotation_x(FRAC_PI_2),
         }
     }
-    fn get(&self, vec: impl Into<Vec3>) -> f32 {
+    pub(crate) fn get(&self, vec: impl Into<Vec3>) -> f32 {
         let v = vec.into();
         match self {
             Vec3Component::X => v.x,
@@ -275,3 +2345,221 @@ impl Vec3Component {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// made up answers for [`query_object`], each taking `delay` polls to come back
+    #[derive(Default)]
+    struct MockObject {
+        delay: usize,
+        transform: Option<Mat4>,
+        bounds: Option<(Vec3, f32)>,
+        local_box: Option<(Vec3, Vec3)>,
+        // `None` for objects without a field
+        ray_march: Option<(f32, f32)>,
+    }
+    impl MockObject {
+        async fn answer<T>(&self, answer: T) -> T {
+            for _ in 0..self.delay {
+                tokio::task::yield_now().await;
+            }
+            answer
+        }
+    }
+    impl ObjectQueries for MockObject {
+        fn has_field(&self) -> bool {
+            self.ray_march.is_some()
+        }
+        async fn transform_to_ray(&self) -> Option<Mat4> {
+            self.answer(self.transform).await
+        }
+        async fn bounding_sphere(&self) -> Option<(Vec3, f32)> {
+            self.answer(self.bounds).await
+        }
+        async fn local_box(&self) -> Option<(Vec3, Vec3)> {
+            self.answer(self.local_box).await
+        }
+        async fn ray_march(&self) -> Option<(f32, f32)> {
+            self.answer(self.ray_march).await
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_scoring_picks_like_sequential() {
+        let at = |x: f32, z: f32| Some(Mat4::from_translation(Vec3::new(x, 0.0, z)));
+        let field = |delay, deepest: f32, center: Vec3, radius| MockObject {
+            delay,
+            bounds: Some((center, radius)),
+            ray_march: Some((-0.01, deepest)),
+            ..Default::default()
+        };
+        let boxed = |delay, z| MockObject {
+            delay,
+            transform: at(0.0, z),
+            local_box: Some((Vec3::ZERO, Vec3::splat(0.2))),
+            ..Default::default()
+        };
+        // answering in a different order than they were asked
+        let objects = [
+            field(3, 2.0, Vec3::new(0.0, 0.0, -2.0), 0.5),
+            field(0, 1.0, Vec3::new(0.1, 0.0, -1.0), 0.3),
+            boxed(5, -1.05),
+            // left out by the broad phase
+            field(1, 1.0, Vec3::new(3.0, 0.0, -1.0), 0.2),
+            // the field isn't hit
+            MockObject {
+                delay: 2,
+                bounds: Some((Vec3::new(0.0, 0.0, -0.5), 0.2)),
+                ray_march: Some((0.1, 0.5)),
+                ..Default::default()
+            },
+            // only the origin to go by
+            MockObject {
+                delay: 4,
+                transform: at(0.02, -1.0),
+                ..Default::default()
+            },
+            // ties with the first box, which comes first
+            boxed(0, -1.05),
+        ];
+        let params = RayParams {
+            origin: Vec3::ZERO,
+            direction: Vec3::NEG_Z,
+            root_ray: Some((Vec3::ZERO, Vec3::NEG_Z)),
+            needs_projection: false,
+            min_selection_distance: 0.0,
+            selection_cone: DEFAULT_SELECTION_CONE,
+            max_range: 10.0,
+        };
+        let concurrent = join_all(
+            objects
+                .iter()
+                .map(|object| query_object(object, (None, None), &params)),
+        )
+        .await;
+        let mut sequential = Vec::new();
+        for object in &objects {
+            sequential.push(query_object(object, (None, None), &params).await);
+        }
+        let scoring = SelectionScoring::default();
+        let scores = |queries: &[RayQuery]| {
+            queries
+                .iter()
+                .map(|query| query.target(&scoring).map(|(_, score)| score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scores(&concurrent), scores(&sequential));
+        let pick = |queries: &[RayQuery]| {
+            best_candidate(
+                queries
+                    .iter()
+                    .zip(0..)
+                    .filter_map(|(query, index)| Some((query.target(&scoring)?.1, index))),
+            )
+            .map(|(_, index)| index)
+        };
+        assert_eq!(pick(&concurrent), Some(2));
+        assert_eq!(pick(&concurrent), pick(&sequential));
+        let hit = scores(&concurrent)
+            .iter()
+            .map(Option::is_some)
+            .collect::<Vec<_>>();
+        assert_eq!(hit, [true, true, true, false, false, true, true]);
+        assert_eq!(pick(&[]), None);
+    }
+
+    /// a lock granted (or refused) after `delay`, `None` for one that never answers,
+    /// and a flag that is set once the lock was released again
+    async fn lock_after(
+        timeout: Duration,
+        delay: Option<Duration>,
+        granted: bool,
+    ) -> (Result<(), CaptureError>, Arc<AtomicBool>) {
+        let unlocked = Arc::new(AtomicBool::new(false));
+        let unlock = unlocked.clone();
+        let locked = lock_within_capture_timeout(
+            timeout,
+            async move {
+                match delay {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => std::future::pending().await,
+                }
+                if granted {
+                    Ok(())
+                } else {
+                    Err(CaptureError::LockHeld)
+                }
+            },
+            move || async move { unlock.store(true, Ordering::Relaxed) },
+        )
+        .await;
+        (locked, unlocked)
+    }
+
+    #[tokio::test]
+    async fn lock_that_never_answers_times_out() {
+        let timeout = Duration::from_millis(10);
+        let started = Instant::now();
+        let (locked, unlocked) = lock_after(timeout, None, true).await;
+        assert!(matches!(locked, Err(CaptureError::TimedOut)));
+        assert!(started.elapsed() >= timeout);
+        tokio::time::sleep(timeout).await;
+        assert!(!unlocked.load(Ordering::Relaxed));
+        // granted after we stopped waiting, so it's released again
+        let (locked, unlocked) = lock_after(timeout, Some(timeout * 2), true).await;
+        assert!(matches!(locked, Err(CaptureError::TimedOut)));
+        tokio::time::sleep(timeout * 4).await;
+        assert!(unlocked.load(Ordering::Relaxed));
+        // someone else holds it, that lock isn't ours to release
+        let (locked, unlocked) = lock_after(timeout, Some(timeout * 2), false).await;
+        assert!(matches!(locked, Err(CaptureError::TimedOut)));
+        tokio::time::sleep(timeout * 4).await;
+        assert!(!unlocked.load(Ordering::Relaxed));
+        // granted in time, nothing to release
+        let (locked, unlocked) = lock_after(timeout, Some(Duration::ZERO), true).await;
+        assert!(locked.is_ok());
+        assert!(!unlocked.load(Ordering::Relaxed));
+        // failing on its own before the timeout keeps the original error
+        let locked =
+            within_capture_timeout(timeout, async { Err::<u32, _>(CaptureError::LockHeld) }).await;
+        assert!(matches!(locked, Err(CaptureError::LockHeld)));
+    }
+
+    #[test]
+    fn shared_highlight_tie_break_is_deterministic() {
+        assert!(first_keeps_highlight(0.2, 0.5));
+        assert!(!first_keeps_highlight(0.5, 0.2));
+        // ties and scores that can't be compared always go to the first selector
+        assert!(first_keeps_highlight(0.3, 0.3));
+        assert!(first_keeps_highlight(f32::INFINITY, f32::INFINITY));
+        assert!(first_keeps_highlight(f32::NAN, 0.1));
+        assert!(first_keeps_highlight(0.1, f32::NAN));
+    }
+
+    #[test]
+    fn target_stays_within_its_size_limits() {
+        let (min, max) = (0.01, 0.5);
+        for fit in [TargetFit::Uniform, TargetFit::PerAxis] {
+            // an object without extent still gets a visible target
+            let (rotation, scale) = target_pose(Vec3::ZERO, fit, min, max);
+            assert!(rotation.is_finite());
+            assert!(
+                scale.abs_diff_eq(Vec3::splat(min * 2.0), 1e-6),
+                "{fit:?} {scale}"
+            );
+            // huge bounds don't blow the target up
+            for huge in [Vec3::splat(1e30), Vec3::new(f32::MAX, 1.0, 1.0)] {
+                let (rotation, scale) = target_pose(huge, fit, min, max);
+                assert!(rotation.is_finite());
+                assert!(scale.is_finite(), "{fit:?} {scale}");
+                assert!(scale.max_element() <= max * 2.0, "{fit:?} {scale}");
+            }
+        }
+        // the short axes of a long object are fit on their own per axis
+        let (_, scale) = target_pose(Vec3::new(0.1, 0.1, 100.0), TargetFit::PerAxis, min, max);
+        assert!((scale.max_element() - (max * 2.0)).abs() < 1e-6);
+        assert!((scale.min_element() - 0.2).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use stardust_xr_fusion::zbus::{
+    Connection,
+    conn::Builder,
+    fdo::{DBusProxy, ObjectManager},
+};
+use stardust_xr_molecules::dbus::AbortOnDrop;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// how often the session bus is checked for still answering
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// wait between attempts to connect to the session bus again
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// connects to the session bus, serving the object manager other clients find our objects with
+pub async fn connect() -> zbus::Result<Connection> {
+    Builder::session()?
+        .serve_at("/", ObjectManager)?
+        .build()
+        .await
+}
+
+/// checks that `conn` still reaches the session bus (e.g. it's gone after a compositor restart)
+/// and connects again once it doesn't, every new connection is sent through the receiver.
+///
+/// stops when the receiver is dropped or the returned task is
+pub fn watch(mut conn: Connection) -> (mpsc::UnboundedReceiver<Connection>, AbortOnDrop) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let task = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            if reachable(&conn).await {
+                continue;
+            }
+            warn!("lost the session bus connection, reconnecting");
+            conn = loop {
+                match connect().await {
+                    Ok(conn) => break conn,
+                    Err(err) => {
+                        warn!(%err, "unable to reconnect to the session bus");
+                        tokio::time::sleep(RECONNECT_INTERVAL).await;
+                    }
+                }
+            };
+            info!("reconnected to the session bus");
+            if tx.send(conn.clone()).is_err() {
+                return;
+            }
+        }
+    });
+    (rx, AbortOnDrop(task.abort_handle()))
+}
+
+async fn reachable(conn: &Connection) -> bool {
+    let Ok(bus) = DBusProxy::new(conn).await else {
+        return false;
+    };
+    bus.get_id().await.is_ok()
+}
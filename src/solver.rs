@@ -0,0 +1,1649 @@
+use std::{
+    f32::consts::{FRAC_PI_2, FRAC_PI_4},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use glam::{Quat, Vec3, Vec3A};
+use stardust_xr_fusion::{
+    ClientHandle,
+    drawable::{
+        Line, LinePoint, Lines, LinesAspect, MaterialParameter, Model, ModelPart, ModelPartAspect,
+    },
+    input::{Hand, InputData, InputDataType},
+    node::{NodeResult, NodeType},
+    objects::object_registry::ObjectRegistry,
+    root::FrameInfo,
+    spatial::{Spatial, SpatialAspect, SpatialRef, Transform},
+    values::{Color, ResourceID, color::rgba_linear},
+    zbus::Connection,
+};
+use stardust_xr_molecules::{
+    accent_color::AccentColor, dbus::AbortOnDrop, input_action::SimpleAction,
+};
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, warn};
+
+use crate::{
+    audio::{Cue, Sounds},
+    filter::{OneEuroFilter, OneEuroSettings},
+    haptics::{HapticOutput, HapticSettings, Haptics},
+    mover::{
+        MoveBounds, MoveConstraint, Mover, MoverMotion, RotationMode, SnapSettings, Throw,
+        predict_trajectory,
+    },
+    recording::InputRecorder,
+    ring::{Ring, RingSettings, RingState},
+    scale_handles::{PinchScale, ScaleHandles},
+    selection::{
+        CaptureError, CapturedSelection, ObjectId, Ray, Selector, SelectorSettings, SolverEvent,
+        snap_to_grid,
+    },
+};
+
+/// length (m) of the pointer ray while nothing is highlighted, see [`SolverSettings::pointer_ray`]
+const POINTER_RAY_LENGTH: f32 = 1.0;
+
+/// how far apart thumb and index tip have to be (m, between the joint surfaces)
+/// for a hand to start and keep levitating.
+///
+/// a wider `enter` than `exit` keeps a spread hovering around the threshold from
+/// flickering between selecting and levitating
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadThresholds {
+    pub enter: f32,
+    pub exit: f32,
+}
+impl Default for SpreadThresholds {
+    fn default() -> Self {
+        Self {
+            enter: 0.02,
+            exit: 0.02,
+        }
+    }
+}
+
+pub struct SolverSettings {
+    /// finger spread that switches a hand from selecting to levitating and back
+    pub spread: SpreadThresholds,
+    /// largest angle (radians) between the palm normal and the selection direction for a hand
+    /// to start levitating, keeps a hand turned away from what it points at from triggering.
+    /// `PI` never blocks
+    pub palm_facing_angle: f32,
+    /// smooths hand tracking jitter out of the fingertips the solver sits on and selects with,
+    /// `None` to use them as tracked
+    pub hand_smoothing: Option<OneEuroSettings>,
+    /// starting to levitate with nothing highlighted captures every object within this radius (m)
+    /// of the hand at once, see [`Selector::capture_within_radius`]. `None` to capture nothing
+    pub sweep_radius: Option<f32>,
+    /// closing the hand into a fist deletes the highlighted object
+    pub fist_delete: bool,
+    /// touching the thumb with the ring finger duplicates the highlighted object,
+    /// see [`Selector::duplicate_selected`]
+    pub duplicate_gesture: bool,
+    /// show a dim solver ring at the hand while nothing is selected
+    pub idle_ring: bool,
+    /// emission multiplier for the idle ring
+    pub idle_ring_emission: f32,
+    /// point with a flat hand (fingertips together, fingers extended) to select along the palm normal,
+    /// spreading the fingers switches back to the triangle to capture the selection
+    pub palm_ray: bool,
+    /// fingertips have to be closer than this to the triangle center for the hand to count as flat
+    pub palm_ray_max_spread: f32,
+    /// the middle fingertip has to be further than this from the palm for the hand to count as flat
+    pub palm_ray_min_reach: f32,
+    /// fall back to any pointer (e.g. a controller) for selecting and its select button for capturing
+    /// while the input the ring is attached to isn't tracked,
+    /// a pointer the ring is attached to is used regardless of this
+    pub pointer_fallback: bool,
+    /// the solver switches to the pointer as soon as the ring's input is lost, but only switches
+    /// back once the ring's input has been tracked for this long, so flickering hand tracking
+    /// doesn't flip between the two every few frames
+    pub pointer_fallback_hysteresis: Duration,
+    /// snap held objects to a world aligned grid, `None` to move them freely
+    pub snap: Option<SnapSettings>,
+    /// box held objects are confined to, `None` to let them move anywhere
+    pub move_bounds: Option<MoveBounds>,
+    /// how held objects catch up with the input
+    pub motion: MoverMotion,
+    /// how held objects turn with the input
+    pub rotation_mode: RotationMode,
+    /// stop held objects at the surfaces of other objects' fields instead of passing through them
+    pub collision: Option<CollisionSettings>,
+    /// how close held objects may get to the head set with [`Solver::set_head`] (m)
+    pub head_clearance: f32,
+    /// input jumps (e.g. tracking teleports) larger than this skip smoothing instead of gliding
+    /// there slowly (m), `None` to always smooth
+    pub jump_threshold: Option<f32>,
+    /// show handles on the faces of held objects that can be pinched with the other hand to
+    /// scale the object along that axis
+    pub scale_handles: bool,
+    /// pinching near held objects with the other hand and then spreading thumb and index apart
+    /// or closing them scales the objects uniformly, see [`PinchScale`].
+    /// while [`SolverSettings::scale_handles`] are dragged it doesn't start and the other way around
+    pub pinch_scale: bool,
+    /// how long nothing gets highlighted after letting go of an object,
+    /// so the dropped object doesn't immediately light up again
+    pub reselect_delay: Duration,
+    /// starting the gesture again this soon after letting go of objects doesn't capture anything,
+    /// so a brief dip in finger spread doesn't grab whatever is nearest right away.
+    /// every release restarts it, `None` to capture right away
+    pub capture_cooldown: Option<Duration>,
+    /// overall glow of the solver models, clamped to `0.0..=4.0`
+    pub emission_intensity: f32,
+    /// how long the solver model takes to glow up when it appears and to fade when it goes away,
+    /// zero to show and hide it instantly
+    pub fade_duration: Duration,
+    /// how fast scrolling (e.g. a controller thumbstick) reels held objects in and pushes them out (m/s)
+    pub distance_scroll_speed: f32,
+    /// with the ring attached to a second hand (see [`RingSettings::bimanual`]), pinching with it
+    /// and moving it away from the levitating hand pushes held objects out by this many meters per
+    /// meter, moving it closer reels them in
+    pub bimanual_distance_gain: f32,
+    /// with the ring attached to a second hand (see [`RingSettings::bimanual`]), pinching with it
+    /// while levitating and then letting go with the levitating hand keeps the objects held by
+    /// the pinching hand until it stops pinching
+    pub hand_off: bool,
+    /// objects let go of while moving keep flying with the velocity they had
+    pub throwing: bool,
+    /// fastest a thrown object may fly (m/s), keeps a jittery frame from launching it across the room
+    pub throw_max_speed: f32,
+    /// how quickly thrown objects slow down, fraction of their velocity lost per second (1/s)
+    pub throw_drag: f32,
+    /// draw the predicted throw trajectory once the levitated object moves faster than this (m/s),
+    /// `None` to never draw it
+    pub throw_arc_min_speed: Option<f32>,
+    /// gravity used for the throw trajectory (m/s²)
+    pub throw_arc_gravity: f32,
+    /// how far into the future the throw trajectory is predicted (s)
+    pub throw_arc_duration: f32,
+    /// draw the selection ray of controllers and tips out to the highlighted object
+    pub pointer_ray: bool,
+    /// write the input used each frame to this file, see [`crate::recording`]
+    pub record_input: Option<PathBuf>,
+    /// play sound cues when the ring attaches or detaches and objects are captured or let go.
+    /// no cues ship with the solver, see [`Sounds`] for where to put them
+    pub sounds: bool,
+    /// vibrate the input the ring is attached to on hover, capture and release,
+    /// see [`Solver::set_haptic_output`]. `None` to never vibrate
+    pub haptics: Option<HapticSettings>,
+}
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            spread: SpreadThresholds::default(),
+            palm_facing_angle: FRAC_PI_2 + FRAC_PI_4,
+            sweep_radius: None,
+            hand_smoothing: Some(OneEuroSettings::default()),
+            fist_delete: false,
+            duplicate_gesture: false,
+            idle_ring: false,
+            idle_ring_emission: 0.25,
+            palm_ray: false,
+            palm_ray_max_spread: 0.015,
+            palm_ray_min_reach: 0.07,
+            pointer_fallback: false,
+            pointer_fallback_hysteresis: Duration::from_millis(500),
+            snap: None,
+            move_bounds: None,
+            motion: MoverMotion::default(),
+            rotation_mode: RotationMode::default(),
+            collision: None,
+            head_clearance: 0.3,
+            jump_threshold: Some(0.5),
+            scale_handles: false,
+            pinch_scale: false,
+            reselect_delay: Duration::from_millis(300),
+            capture_cooldown: Some(Duration::from_millis(200)),
+            emission_intensity: 1.0,
+            fade_duration: Duration::from_millis(150),
+            distance_scroll_speed: 1.0,
+            bimanual_distance_gain: 4.0,
+            hand_off: false,
+            throwing: false,
+            throw_max_speed: 5.0,
+            throw_drag: 3.0,
+            throw_arc_min_speed: None,
+            throw_arc_gravity: 9.81,
+            throw_arc_duration: 0.5,
+            pointer_ray: true,
+            record_input: None,
+            sounds: false,
+            haptics: Some(HapticSettings::default()),
+        }
+    }
+}
+
+/// see [`SolverSettings::collision`]
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionSettings {
+    /// only fields whose bounds are within this distance (m) of a held object can block it
+    pub range: f32,
+    /// most fields tested against each held object per frame, the nearest ones win
+    pub max_fields: usize,
+}
+impl Default for CollisionSettings {
+    fn default() -> Self {
+        Self {
+            range: 0.5,
+            max_fields: 4,
+        }
+    }
+}
+
+/// everything that can be tuned about the solver, the defaults are what the solver always did
+#[derive(Default)]
+pub struct SolverConfig {
+    pub solver: SolverSettings,
+    pub ring: RingSettings,
+    pub selector: SelectorSettings,
+}
+
+/// an object currently held by the solver
+struct Capture {
+    mover: Mover,
+    captured_at: Instant,
+    // gesture captures are released when the gesture stops
+    from_gesture: bool,
+    // held by the second hand's pinch since the levitating hand let go, see [`SolverSettings::hand_off`]
+    handed_off: bool,
+    scale_handles: Option<ScaleHandles>,
+    pinch_scale: Option<PinchScale>,
+}
+
+/// where the solver currently gets its input from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputSource {
+    /// the input the ring is attached to
+    Ring,
+    /// any pointer the ring's input handler receives, see [`SolverSettings::pointer_fallback`]
+    Pointer,
+}
+
+/// what the solver tracks about the gestures in progress, starts over whenever the solver is
+/// disabled so enabling it again doesn't pick up where a gesture left off
+#[derive(Debug)]
+struct GestureState {
+    input_source: InputSource,
+    // since when the ring's input has been tracked again while falling back to the pointer
+    ring_input_since: Option<Instant>,
+    // filters for the fingertips of `GestureHand::triangle` and whether they filtered a right hand
+    fingertip_filters: [OneEuroFilter; 3],
+    filtered_hand: Option<bool>,
+    // distance between the levitating hand and the pinching second hand last frame
+    bimanual_separation: Option<f32>,
+    // seconds of frame time until captures are allowed again, see [`SolverSettings::capture_cooldown`]
+    capture_cooldown: f32,
+}
+impl Default for GestureState {
+    fn default() -> Self {
+        Self {
+            input_source: InputSource::Ring,
+            ring_input_since: None,
+            fingertip_filters: Default::default(),
+            filtered_hand: None,
+            bimanual_separation: None,
+            capture_cooldown: 0.0,
+        }
+    }
+}
+impl GestureState {
+    /// forgets the gestures in progress, see [`Solver::set_enabled`]
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+struct GrabPose {
+    center: Vec3,
+    rotation: Quat,
+    diameter: f32,
+    selection_origin: Vec3,
+    selection_dir: Vec3,
+}
+
+/// a snapshot of a held object, see [`Solver::active_captures`]
+#[derive(Debug, Clone)]
+pub struct CaptureHandle {
+    pub id: ObjectId,
+    /// last known position relative to the client root
+    pub position: Option<Vec3A>,
+    /// distance between the input and the object in meters
+    pub distance: f32,
+    /// how long the object has been held
+    pub held_for: Duration,
+}
+
+pub struct Solver {
+    settings: SolverSettings,
+    pub ring: Ring,
+    selector: Selector,
+    lines: Lines,
+    input_spatial: Spatial,
+    // follows the second hand's pinch, see [`SolverSettings::hand_off`]
+    secondary_spatial: Spatial,
+    solver_active: SimpleAction,
+    group_toggle: SimpleAction,
+    fist: SimpleAction,
+    duplicate: SimpleAction,
+    solver_model: Model,
+    solver_part: ModelPart,
+    solver_target_model: Model,
+    solver_target_part: ModelPart,
+    solver_color_tx: watch::Sender<Option<Color>>,
+    solver_color: watch::Receiver<Option<Color>>,
+    ring_color: watch::Receiver<Option<Color>>,
+    solver_emission: f32,
+    // how far the solver model has faded in and the intensity it fades to or from
+    solver_fade: f32,
+    solver_fade_intensity: f32,
+    captures: Vec<Capture>,
+    move_constraint: MoveConstraint,
+    throws: Vec<Throw>,
+    gesture: GestureState,
+    head: Option<SpatialRef>,
+    last_release: Option<Instant>,
+    last_interaction: Instant,
+    recorder: Option<InputRecorder>,
+    sounds: Sounds,
+    haptics: Option<Haptics>,
+    enabled: bool,
+    enabled_requests: watch::Receiver<bool>,
+    enabled_tx: watch::Sender<bool>,
+    _accent_color_task: AbortOnDrop,
+}
+
+/// lets other clients toggle the solver over D-Bus
+struct SolverInterface {
+    enabled: watch::Sender<bool>,
+}
+#[zbus::interface(name = "org.stardustxr.AbsoluteSolver")]
+impl SolverInterface {
+    fn set_enabled(&self, enabled: bool) {
+        self.enabled.send_replace(enabled);
+    }
+    #[zbus(property)]
+    fn enabled(&self) -> bool {
+        *self.enabled.borrow()
+    }
+}
+
+async fn serve_interface(conn: &Connection, enabled: &watch::Sender<bool>) {
+    if let Err(err) = conn
+        .object_server()
+        .at(
+            "/org/stardustxr/AbsoluteSolver",
+            SolverInterface {
+                enabled: enabled.clone(),
+            },
+        )
+        .await
+    {
+        warn!(%err, "unable to serve the solver D-Bus interface");
+    }
+}
+
+impl Solver {
+    pub async fn new(
+        client: Arc<ClientHandle>,
+        conn: Connection,
+        object_registry: Arc<ObjectRegistry>,
+        config: SolverConfig,
+        ring_state: Option<RingState>,
+    ) -> NodeResult<Self> {
+        let SolverConfig {
+            solver: settings,
+            ring: ring_settings,
+            selector: selector_settings,
+        } = config;
+        let lines = Lines::create(client.get_root(), Transform::none(), &[])?;
+        let (enabled_tx, enabled_requests) = watch::channel(true);
+        serve_interface(&conn, &enabled_tx).await;
+        let recorder = settings.record_input.as_deref().and_then(|path| {
+            InputRecorder::create(path)
+                .inspect_err(|err| warn!(%err, ?path, "unable to create input recording"))
+                .ok()
+        });
+        let ring = Ring::new(conn.clone(), &client, ring_settings, ring_state)?;
+
+        let input_spatial = Spatial::create(client.get_root(), Transform::none())?;
+        let secondary_spatial = Spatial::create(client.get_root(), Transform::none())?;
+
+        let solver_model = Model::create(
+            client.get_root(),
+            Transform::identity(),
+            &ResourceID::new_namespaced("absolute_solver", "solver"),
+        )?;
+        _ = solver_model.set_enabled(false);
+
+        let solver_target_model = Model::create(
+            client.get_root(),
+            Transform::identity(),
+            &ResourceID::new_namespaced("absolute_solver", "solver"),
+        )?;
+        _ = solver_target_model.set_enabled(false);
+
+        let selector = Selector::new(
+            client.clone(),
+            object_registry,
+            solver_target_model.clone(),
+            selector_settings,
+        )
+        .await?;
+        let haptics = settings
+            .haptics
+            .map(|haptics| Haptics::new(haptics, selector.events()));
+
+        // change solver color to match accent color
+        let solver_part = solver_model.part("Solver")?;
+        let solver_target_part = solver_target_model.part("Solver")?;
+        let (solver_color_tx, solver_color) = watch::channel(None::<Color>);
+        let accent_color_task = follow_accent_color(
+            conn,
+            settings.emission_intensity,
+            solver_color_tx.clone(),
+            solver_target_part.clone(),
+        );
+
+        Ok(Self {
+            settings,
+            ring,
+            selector,
+            lines,
+            input_spatial,
+            secondary_spatial,
+            solver_active: SimpleAction::default(),
+            group_toggle: SimpleAction::default(),
+            fist: SimpleAction::default(),
+            duplicate: SimpleAction::default(),
+            solver_model,
+            solver_part,
+            solver_target_model,
+            solver_target_part,
+            solver_color_tx,
+            ring_color: solver_color.clone(),
+            solver_color,
+            solver_emission: 1.0,
+            solver_fade: 0.0,
+            solver_fade_intensity: 1.0,
+            captures: Vec::new(),
+            move_constraint: MoveConstraint::default(),
+            throws: Vec::new(),
+            gesture: GestureState::default(),
+            head: None,
+            last_release: None,
+            last_interaction: Instant::now(),
+            recorder,
+            sounds: Sounds::default(),
+            haptics,
+            enabled: true,
+            enabled_requests,
+            enabled_tx,
+            _accent_color_task: accent_color_task,
+        })
+    }
+
+    /// where the pulses of [`SolverSettings::haptics`] go, nothing vibrates until this is set
+    /// since input methods don't expose haptic output themselves yet
+    pub fn set_haptic_output(&mut self, output: Box<dyn HapticOutput>) {
+        if let Some(haptics) = &mut self.haptics {
+            haptics.set_output(output);
+        }
+    }
+
+    /// keeps held objects away from the user's head, `None` to let them come arbitrarily close
+    pub fn set_head(&mut self, head: Option<SpatialRef>) {
+        for capture in &mut self.captures {
+            capture
+                .mover
+                .set_head(head.clone(), self.settings.head_clearance);
+        }
+        self.head = head;
+    }
+
+    /// limits how held objects can move, for this and all future captures
+    pub fn set_move_constraint(&mut self, constraint: MoveConstraint) {
+        for capture in &mut self.captures {
+            capture.mover.set_constraint(constraint);
+        }
+        self.move_constraint = constraint;
+    }
+
+    /// hover, capture and release events of all objects the solver touches
+    pub fn events(&self) -> broadcast::Receiver<SolverEvent> {
+        self.selector.events()
+    }
+
+    /// the highlighted object, see [`Selector::current_selection`]
+    pub fn current_selection(&self) -> Option<ObjectId> {
+        self.selector.current_selection()
+    }
+
+    /// what the last ray could select and how it scored, see [`Selector::candidates`]
+    pub fn selection_candidates(&self) -> Vec<(ObjectId, f32)> {
+        self.selector.candidates()
+    }
+
+    /// when something was last selected or captured (monotonic clock),
+    /// starts out as the time the solver was created
+    pub fn last_interaction_time(&self) -> Instant {
+        self.last_interaction
+    }
+
+    /// all objects currently held by the solver
+    pub fn active_captures(&self) -> Vec<CaptureHandle> {
+        let now = Instant::now();
+        self.captures
+            .iter()
+            .map(|capture| CaptureHandle {
+                id: capture.mover.selection().id().clone(),
+                position: capture.mover.position(),
+                distance: capture.mover.distance(),
+                held_for: now.duration_since(capture.captured_at),
+            })
+            .collect()
+    }
+
+    /// captures a specific object without a gesture, it stays held until it's released with
+    /// [`Solver::release_capture`] or the ring loses its input
+    pub async fn capture(&mut self, object: &ObjectId) -> Result<(), CaptureError> {
+        let selection = self.selector.capture(object).await?;
+        self.add_capture(selection, false)
+            .await
+            .map_err(|_| CaptureError::ReparentFailed)
+    }
+
+    /// releases only the object with the given id, returns `false` if it wasn't held
+    pub fn release_capture(&mut self, id: &ObjectId) -> bool {
+        let Some(index) = self
+            .captures
+            .iter()
+            .position(|capture| capture.mover.selection().id() == id)
+        else {
+            return false;
+        };
+        self.captures.remove(index);
+        true
+    }
+
+    /// while disabled no frames are processed and everything is hidden,
+    /// held objects are released instead of freezing in place
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled == enabled {
+            return;
+        }
+        self.enabled = enabled;
+        self.ring.set_enabled(enabled);
+        if !enabled {
+            self.captures.clear();
+            self.throws.clear();
+            self.gesture.reset();
+            self.selector.clear_selection();
+            self.selector.clear_group();
+            _ = self.lines.set_lines(&[]);
+            _ = self.solver_model.set_enabled(false);
+            self.solver_fade = 0.0;
+            _ = self.solver_target_model.set_enabled(false);
+        }
+    }
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    /// lets go of everything held or still flying and waits until all of it was handed back,
+    /// call this before exiting so no object is left parented to the solver
+    pub async fn shutdown(&mut self) {
+        let held = std::mem::take(&mut self.captures)
+            .into_iter()
+            .map(|capture| capture.mover.into_selection());
+        let thrown = std::mem::take(&mut self.throws)
+            .into_iter()
+            .map(Throw::into_selection);
+        join_all(held.chain(thrown).map(CapturedSelection::release)).await;
+    }
+    /// moves the solver's D-Bus interface and object queries over to a new session bus
+    /// connection after the old one was lost, see [`crate::session::watch`].
+    ///
+    /// held objects stay held, the ring is rebuilt in place and the accent color is followed
+    /// on the new connection
+    pub async fn reconnect(&mut self, conn: Connection, object_registry: Arc<ObjectRegistry>) {
+        serve_interface(&conn, &self.enabled_tx).await;
+        self.selector.reconnect(object_registry);
+        if let Err(err) = self.ring.reconnect(conn.clone()) {
+            warn!(%err, "unable to rebuild the ring on the new connection");
+        }
+        if !self.enabled {
+            self.ring.set_enabled(false);
+        }
+        if let Some(color) = *self.ring_color.borrow() {
+            self.ring.set_accent_color(color);
+        }
+        self._accent_color_task = follow_accent_color(
+            conn,
+            self.settings.emission_intensity,
+            self.solver_color_tx.clone(),
+            self.solver_target_part.clone(),
+        );
+    }
+
+    pub async fn frame(&mut self, frame_info: &FrameInfo) {
+        if self.enabled_requests.has_changed().unwrap_or_default() {
+            let enabled = *self.enabled_requests.borrow_and_update();
+            self.set_enabled(enabled);
+        }
+        if !self.enabled {
+            return;
+        }
+        let was_attached = self.ring.is_attached();
+        self.ring.update(frame_info);
+        if self.settings.sounds && self.ring.is_attached() != was_attached {
+            let cue = if was_attached {
+                Cue::Detach
+            } else {
+                Cue::Attach
+            };
+            self.sounds
+                .play(cue, self.lines.client().get_root(), self.ring.position());
+        }
+        if let Some(haptics) = &mut self.haptics {
+            haptics.update(self.ring.get_attached_input().as_deref());
+        }
+        for throw in &mut self.throws {
+            throw
+                .update(frame_info.delta, self.settings.throw_drag)
+                .await;
+        }
+        self.throws.retain(Throw::is_moving);
+        self.gesture.capture_cooldown = (self.gesture.capture_cooldown - frame_info.delta).max(0.0);
+        if self.solver_color.has_changed().unwrap_or_default() {
+            self.solver_emission = -1.0;
+        }
+        if self.ring_color.has_changed().unwrap_or_default()
+            && let Some(color) = *self.ring_color.borrow_and_update()
+        {
+            self.ring.set_accent_color(color);
+            self.selector.set_accent_color(color);
+        }
+
+        let input = self.current_input();
+        if let Some(recorder) = self.recorder.as_mut()
+            && let Err(err) = recorder.record(input.as_deref())
+        {
+            warn!(%err, "unable to record input, stopping the recording");
+            self.recorder = None;
+        }
+        let Some(input) = input else {
+            self.gesture.filtered_hand = None;
+            _ = self.lines.set_lines(&[]);
+            self.fade_solver_model(false, frame_info.delta);
+            self.captures.clear();
+            _ = self.solver_target_model.set_enabled(false);
+            return;
+        };
+        let spread = self.settings.spread;
+        let palm_facing_angle = self.settings.palm_facing_angle;
+        let acting = self.solver_active.currently_acting().clone();
+        self.solver_active
+            .update(&self.ring.input, &|data| match &data.input {
+                InputDataType::Pointer(_) => {
+                    data.datamap.with_data(|d| d.idx("select").as_f32() > 0.5)
+                }
+                InputDataType::Hand(hand) => GestureHand::from(hand).levitating(
+                    acting.contains(data),
+                    &spread,
+                    palm_facing_angle,
+                ),
+                InputDataType::Tip(_) => data.datamap.with_data(|d| d.idx("grab").as_f32() > 0.5),
+            });
+
+        let mut lines_data = Vec::new();
+        let Some(GrabPose {
+            center: triangle_center,
+            rotation,
+            diameter,
+            selection_origin,
+            selection_dir,
+        }) = self.grab_pose(&input, frame_info.delta, &mut lines_data)
+        else {
+            return;
+        };
+        let normal = rotation * Vec3::NEG_Z;
+        lines_data.push(Line {
+            points: vec![
+                LinePoint {
+                    point: triangle_center.into(),
+                    thickness: 0.001,
+                    color: rgba_linear!(0.0, 1.0, 0.0, 1.0),
+                },
+                LinePoint {
+                    point: (triangle_center + (normal * 0.01)).into(),
+                    thickness: 0.001,
+                    color: rgba_linear!(0.0, 1.0, 0.0, 1.0),
+                },
+            ],
+            cyclic: false,
+        });
+        lines_data.push(Line {
+            points: vec![
+                LinePoint {
+                    point: triangle_center.into(),
+                    thickness: 0.001,
+                    color: rgba_linear!(0.0, 0.0, 1.0, 1.0),
+                },
+                LinePoint {
+                    point: (triangle_center + (selection_dir * 0.01)).into(),
+                    thickness: 0.001,
+                    color: rgba_linear!(0.0, 0.0, 1.0, 1.0),
+                },
+            ],
+            cyclic: false,
+        });
+
+        _ = self
+            .input_spatial
+            .set_local_transform(Transform::from_translation_rotation(triangle_center, {
+                let ref_quat = rotation;
+                ref_quat * Quat::from_rotation_arc(Vec3::NEG_Z, ref_quat.inverse() * normal)
+            }));
+
+        // controllers can gather several objects with their secondary button to move them together
+        self.group_toggle
+            .update(&self.ring.input, &|data| match &data.input {
+                InputDataType::Hand(_) => false,
+                _ => data
+                    .datamap
+                    .with_data(|d| d.idx("secondary").as_f32() > 0.5),
+            });
+        if self.group_toggle.started_acting().contains(&input) {
+            self.selector.toggle_add_to_selection();
+        }
+        self.fist
+            .update(&self.ring.input, &|data| match &data.input {
+                InputDataType::Hand(hand) => {
+                    let palm = Vec3::from(hand.palm.position);
+                    [&hand.index, &hand.middle, &hand.ring, &hand.little]
+                        .iter()
+                        .all(|finger| palm.distance(finger.tip.position.into()) < 0.05)
+                }
+                _ => false,
+            });
+        if self.settings.fist_delete
+            && self.fist.started_acting().contains(&input)
+            && !self.solver_active.currently_acting().contains(&input)
+        {
+            self.selector.delete_selected().await;
+        }
+        self.duplicate
+            .update(&self.ring.input, &|data| match &data.input {
+                InputDataType::Hand(hand) => {
+                    Vec3::from(hand.thumb.tip.position).distance(hand.ring.tip.position.into())
+                        - (hand.thumb.tip.radius + hand.ring.tip.radius)
+                        < 0.005
+                }
+                _ => false,
+            });
+        if self.settings.duplicate_gesture
+            && self.duplicate.started_acting().contains(&input)
+            && !self.solver_active.currently_acting().contains(&input)
+        {
+            self.selector.duplicate_selected().await;
+        }
+        if self.solver_active.started_acting().contains(&input)
+            && self.gesture.capture_cooldown <= 0.0
+        {
+            let captured = if self.selector.group_len() > 0 {
+                self.selector.capture_group().await.map(Ok)
+            } else if !self.selector.has_selection()
+                && let Some(radius) = self.settings.sweep_radius
+            {
+                let sweep = self
+                    .selector
+                    .capture_within_radius(triangle_center, radius, self.ring.input.handler())
+                    .await;
+                for (id, err) in &sweep.skipped {
+                    debug!(%id, %err, "unable to sweep up object");
+                }
+                sweep.captured.map(Ok)
+            } else {
+                self.selector.capture_selected().await
+            };
+            match captured {
+                Some(Ok(sel)) => {
+                    if self.settings.sounds {
+                        self.sounds.play(Cue::Capture, sel.spatial(), Vec3::ZERO);
+                    }
+                    _ = self.add_capture(sel, true).await;
+                }
+                // let the user know the object is busy instead of silently doing nothing
+                Some(Err(err)) => {
+                    debug!(%err, "unable to capture the selection");
+                    self.selector.highlight_failure().await;
+                }
+                None => {}
+            }
+        }
+        // we can use this solver active with containing input to get when we start and stop expanding our fingers to be able to switch between selection and levitation
+        let solver_transform = Transform::from_translation_rotation_scale(
+            triangle_center + (normal * 0.01),
+            rotation * Quat::from_rotation_x(FRAC_PI_2),
+            [diameter * 2.0; 3],
+        );
+        if self.solver_active.currently_acting().contains(&input) {
+            // TODO: replace with actual transform functionality
+            let scroll = match &input.input {
+                InputDataType::Hand(_) => 0.0,
+                _ => input
+                    .datamap
+                    .with_data(|d| d.idx("scroll").as_vector().idx(1).as_f32()),
+            };
+            if scroll != 0.0 {
+                let delta = scroll * self.settings.distance_scroll_speed * frame_info.delta;
+                for capture in &mut self.captures {
+                    capture.mover.adjust_distance(delta);
+                }
+            }
+            let separation = self
+                .ring
+                .get_secondary_input()
+                .and_then(|secondary| match &secondary.input {
+                    InputDataType::Hand(hand) if pinching(hand) => {
+                        Some(Vec3::from(hand.index.tip.position).distance(triangle_center))
+                    }
+                    _ => None,
+                });
+            if let Some(separation) = separation
+                && let Some(last) = self.gesture.bimanual_separation
+            {
+                let delta = (separation - last) * self.settings.bimanual_distance_gain;
+                for capture in &mut self.captures {
+                    capture.mover.adjust_distance(delta);
+                }
+            }
+            self.gesture.bimanual_separation = separation;
+            self.update_captures(frame_info.delta, &mut lines_data)
+                .await;
+            self.solver_fade_intensity = 1.0;
+            self.fade_solver_model(!self.captures.is_empty(), frame_info.delta);
+            self.solver_model
+                .set_local_transform(solver_transform)
+                .unwrap();
+        } else {
+            let secondary_pinch = self.secondary_pinch();
+            if let Some((position, rotation)) = secondary_pinch {
+                _ = self
+                    .secondary_spatial
+                    .set_local_transform(Transform::from_translation_rotation(position, rotation));
+            }
+            // both hands held the objects and the levitating one let go
+            if self.settings.hand_off
+                && self.gesture.bimanual_separation.is_some()
+                && secondary_pinch.is_some()
+            {
+                self.hand_off_captures().await;
+            }
+            self.gesture.bimanual_separation = None;
+            let (released, held): (Vec<_>, Vec<_>) = std::mem::take(&mut self.captures)
+                .into_iter()
+                .partition(|capture| {
+                    capture.from_gesture || (capture.handed_off && secondary_pinch.is_none())
+                });
+            self.captures = held;
+            if !released.is_empty() {
+                self.last_release = Some(Instant::now());
+                if let Some(cooldown) = self.settings.capture_cooldown {
+                    self.gesture.capture_cooldown = cooldown.as_secs_f32();
+                }
+            }
+            if self.settings.sounds {
+                for position in released
+                    .iter()
+                    .filter_map(|capture| capture.mover.position())
+                {
+                    self.sounds.play(
+                        Cue::Release,
+                        self.lines.client().get_root(),
+                        position.into(),
+                    );
+                }
+            }
+            for capture in released {
+                let throw = capture.mover.release(self.settings.throw_max_speed);
+                if self.settings.throwing && throw.is_moving() {
+                    self.throws.push(throw);
+                } else {
+                    self.selector.dock_to_surface(throw.selection()).await;
+                }
+            }
+            self.update_captures(frame_info.delta, &mut lines_data)
+                .await;
+            if self
+                .last_release
+                .is_some_and(|released| released.elapsed() < self.settings.reselect_delay)
+            {
+                self.selector.clear_selection();
+            } else {
+                self.selector
+                    .update_selection(Ray {
+                        origin: selection_origin,
+                        direction: selection_dir,
+                        ref_space: self.ring.input.handler().clone().as_spatial_ref(),
+                    })
+                    .await;
+                if self.selector.has_selection() {
+                    self.last_interaction = Instant::now();
+                }
+            }
+            if self.settings.pointer_ray && !matches!(input.input, InputDataType::Hand(_)) {
+                let length = match self.selector.selection_position().await {
+                    Some(position) => (position - selection_origin).dot(selection_dir).max(0.0),
+                    None => POINTER_RAY_LENGTH,
+                };
+                let color = if !self.selector.has_selection() {
+                    rgba_linear!(1.0, 1.0, 1.0, 0.2)
+                } else if self.selector.selection_armed() {
+                    rgba_linear!(1.0, 1.0, 1.0, 1.0)
+                } else {
+                    rgba_linear!(1.0, 1.0, 1.0, 0.5)
+                };
+                lines_data.push(Line {
+                    points: [
+                        selection_origin,
+                        selection_origin + (selection_dir * length),
+                    ]
+                    .into_iter()
+                    .map(|point| LinePoint {
+                        point: point.into(),
+                        thickness: 0.001,
+                        color,
+                    })
+                    .collect(),
+                    cyclic: false,
+                });
+            }
+            let show_idle_ring = self.settings.idle_ring && !self.selector.has_selection();
+            if show_idle_ring {
+                self.solver_fade_intensity = self.settings.idle_ring_emission;
+            }
+            self.fade_solver_model(show_idle_ring, frame_info.delta);
+            if self.solver_fade > 0.0 {
+                self.solver_model
+                    .set_local_transform(solver_transform)
+                    .unwrap();
+            }
+        }
+        self.lines.set_lines(&lines_data).unwrap();
+    }
+
+    /// the input to select and levitate with this frame, see [`SolverSettings::pointer_fallback`]
+    fn current_input(&mut self) -> Option<Arc<InputData>> {
+        let ring_input = self.ring.get_attached_input();
+        if !self.settings.pointer_fallback {
+            return ring_input;
+        }
+        let pointer = self
+            .ring
+            .input
+            .input()
+            .into_iter()
+            .map(|(input, _)| input)
+            .find(|input| matches!(input.input, InputDataType::Pointer(_)));
+        match self.gesture.input_source {
+            InputSource::Ring if ring_input.is_none() && pointer.is_some() => {
+                self.gesture.input_source = InputSource::Pointer;
+                self.gesture.ring_input_since = None;
+            }
+            InputSource::Pointer if ring_input.is_some() => {
+                let since = *self
+                    .gesture
+                    .ring_input_since
+                    .get_or_insert_with(Instant::now);
+                if pointer.is_none() || since.elapsed() >= self.settings.pointer_fallback_hysteresis
+                {
+                    self.gesture.input_source = InputSource::Ring;
+                    self.gesture.ring_input_since = None;
+                }
+            }
+            InputSource::Pointer => self.gesture.ring_input_since = None,
+            InputSource::Ring => {}
+        }
+        match self.gesture.input_source {
+            InputSource::Ring => ring_input,
+            InputSource::Pointer => pointer,
+        }
+    }
+
+    /// where the second hand the ring is attached to pinches, `None` while it doesn't
+    fn secondary_pinch(&self) -> Option<(Vec3, Quat)> {
+        let secondary = self.ring.get_secondary_input()?;
+        match &secondary.input {
+            InputDataType::Hand(hand) if pinching(hand) => Some((
+                Vec3::from(hand.thumb.tip.position).lerp(hand.index.tip.position.into(), 0.5),
+                hand.palm.rotation.into(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// moves the gesture captures over to the second hand's pinch, see [`SolverSettings::hand_off`]
+    async fn hand_off_captures(&mut self) {
+        let secondary = self.secondary_spatial.clone().as_spatial_ref();
+        let mut captures = Vec::with_capacity(self.captures.len());
+        for mut capture in std::mem::take(&mut self.captures) {
+            if !capture.from_gesture {
+                captures.push(capture);
+                continue;
+            }
+            match capture.mover.hand_off(secondary.clone()).await {
+                Ok(mover) => {
+                    capture.mover = mover;
+                    capture.from_gesture = false;
+                    capture.handed_off = true;
+                    captures.push(capture);
+                }
+                Err(err) => debug!(%err, "unable to hand off held object, letting go of it"),
+            }
+        }
+        self.captures = captures;
+    }
+
+    async fn add_capture(
+        &mut self,
+        selection: CapturedSelection,
+        from_gesture: bool,
+    ) -> NodeResult<()> {
+        let mut mover = Mover::new(selection, self.input_spatial.clone().as_spatial_ref()).await?;
+        let motion = match mover.selection().mass() {
+            Some(mass) => self.settings.motion.with_mass(mass),
+            None => self.settings.motion,
+        };
+        mover.set_motion(motion);
+        mover.set_rotation_mode(self.settings.rotation_mode);
+        mover.set_head(self.head.clone(), self.settings.head_clearance);
+        mover.set_jump_threshold(self.settings.jump_threshold);
+        mover.set_bounds(self.settings.move_bounds.clone());
+        mover.set_constraint(self.move_constraint);
+        mover.set_snap(self.settings.snap);
+        let scale_handles = if self.settings.scale_handles {
+            let (center, size) = mover.selection().bounds();
+            Some(ScaleHandles::create(
+                mover.selection().spatial(),
+                center,
+                size,
+            )?)
+        } else {
+            None
+        };
+        let pinch_scale = if self.settings.pinch_scale {
+            let (center, size) = mover.selection().bounds();
+            Some(PinchScale::create(
+                mover.selection().spatial(),
+                center,
+                size,
+            )?)
+        } else {
+            None
+        };
+        self.last_interaction = Instant::now();
+        self.captures.push(Capture {
+            mover,
+            captured_at: Instant::now(),
+            from_gesture,
+            handed_off: false,
+            scale_handles,
+            pinch_scale,
+        });
+        Ok(())
+    }
+
+    async fn update_captures(&mut self, delta: f32, lines_data: &mut Vec<Line>) {
+        // objects destroyed while held can't be moved anymore
+        let mut captures = Vec::with_capacity(self.captures.len());
+        for capture in std::mem::take(&mut self.captures) {
+            let id = capture.mover.selection().id();
+            if self.selector.contains(id).await {
+                captures.push(capture);
+            } else {
+                debug!(%id, "held object is gone, letting go of it");
+            }
+        }
+        self.captures = captures;
+        for capture in &mut self.captures {
+            // only one of them scales the object at a time, whichever started first
+            let current_scale = capture.mover.scale();
+            let pinch_dragging = capture
+                .pinch_scale
+                .as_ref()
+                .is_some_and(PinchScale::dragging);
+            let handle_scale = capture
+                .scale_handles
+                .as_mut()
+                .and_then(|scale_handles| scale_handles.update(current_scale, !pinch_dragging));
+            let handles_dragging = capture
+                .scale_handles
+                .as_ref()
+                .is_some_and(ScaleHandles::dragging);
+            let pinch_scale = capture
+                .pinch_scale
+                .as_mut()
+                .and_then(|pinch_scale| pinch_scale.update(current_scale, !handles_dragging));
+            let scale = handle_scale.or(pinch_scale);
+            if let Some(scale) = scale {
+                capture.mover.set_scale(scale);
+            }
+            if let Some(collision) = &self.settings.collision {
+                let obstacles = self
+                    .selector
+                    .nearby_fields(
+                        capture.mover.selection(),
+                        collision.range,
+                        collision.max_fields,
+                    )
+                    .await;
+                capture.mover.set_obstacles(obstacles);
+            }
+            capture.mover.update(delta).await;
+            // shows where the object is going to snap to once it's let go
+            if let Some(snap) = &self.settings.snap
+                && snap.on_release_only
+                && snap.translation_step > 0.0
+                && let Some(position) = capture.mover.position()
+            {
+                lines_data.extend(grid_hint(position.into(), snap.translation_step));
+            }
+            let velocity = Vec3::from(capture.mover.velocity());
+            if let Some(min_speed) = self.settings.throw_arc_min_speed
+                && let Some(position) = capture.mover.position()
+                && velocity.length() > min_speed
+            {
+                lines_data.push(Line {
+                    points: predict_trajectory(
+                        position.into(),
+                        velocity,
+                        self.settings.throw_arc_gravity,
+                        self.settings.throw_arc_duration,
+                        16,
+                    )
+                    .into_iter()
+                    .map(|p| LinePoint {
+                        point: p.into(),
+                        thickness: 0.0025,
+                        color: rgba_linear!(1.0, 1.0, 1.0, 0.5),
+                    })
+                    .collect(),
+                    cyclic: false,
+                });
+            }
+        }
+    }
+
+    /// where the solver sits on the input and where it selects from
+    fn grab_pose(
+        &mut self,
+        input: &InputData,
+        delta: f32,
+        lines_data: &mut Vec<Line>,
+    ) -> Option<GrabPose> {
+        if !matches!(input.input, InputDataType::Hand(_)) {
+            self.gesture.filtered_hand = None;
+        }
+        match &input.input {
+            InputDataType::Tip(tip) => Some(GrabPose {
+                center: tip.origin.into(),
+                rotation: tip.orientation.into(),
+                diameter: 0.1,
+                selection_origin: tip.origin.into(),
+                selection_dir: Quat::from(tip.orientation) * Vec3::NEG_Z,
+            }),
+            InputDataType::Hand(hand) => {
+                let p = self.smoothed_triangle(hand, delta);
+                lines_data.push(Line {
+                    points: p
+                        .iter()
+                        .copied()
+                        .map(|p| LinePoint {
+                            point: p.into(),
+                            thickness: 0.001,
+                            color: rgba_linear!(1.0, 0.0, 1.0, 1.0),
+                        })
+                        .collect(),
+                    cyclic: true,
+                });
+                // fingertips pressed together or badly tracked don't span a triangle
+                let (position, rotation) =
+                    get_position_and_normal_from_triangle(p, hand.palm.rotation.into())
+                        .unwrap_or((hand.palm.position.into(), hand.palm.rotation.into()));
+                let max_distance_from_center = p
+                    .iter()
+                    .map(|point| point.distance(position))
+                    .reduce(|a, b| if a > b { a } else { b })
+                    .unwrap_or_default();
+                let palm = Vec3::from(hand.palm.position);
+                let flat_hand = self.settings.palm_ray
+                    && max_distance_from_center < self.settings.palm_ray_max_spread
+                    && palm.distance(hand.middle.tip.position.into())
+                        > self.settings.palm_ray_min_reach;
+                let (selection_origin, selection_dir) = if flat_hand {
+                    (palm, Quat::from(hand.palm.rotation) * Vec3::NEG_Y)
+                } else {
+                    (position, (position - palm).normalize())
+                };
+                Some(GrabPose {
+                    center: position,
+                    rotation,
+                    diameter: max_distance_from_center * 2.0,
+                    selection_origin,
+                    selection_dir,
+                })
+            }
+            InputDataType::Pointer(pointer) => Some(GrabPose {
+                center: pointer.origin.into(),
+                rotation: pointer.orientation.into(),
+                diameter: 0.1,
+                selection_origin: pointer.origin.into(),
+                selection_dir: Quat::from(pointer.orientation) * Vec3::NEG_Z,
+            }),
+        }
+    }
+
+    /// [`GestureHand::triangle`] run through [`SolverSettings::hand_smoothing`]
+    fn smoothed_triangle(&mut self, hand: &Hand, delta: f32) -> [Vec3; 3] {
+        let points = GestureHand::from(hand).triangle();
+        let Some(smoothing) = self.settings.hand_smoothing else {
+            return points;
+        };
+        // switching hands or tracking teleporting the hand would otherwise glide the solver
+        // from where it was to where the hand is now
+        let jumped = self
+            .fingertip_filters
+            .iter()
+            .zip(points)
+            .any(|(filter, point)| filter.jumps_to(point, self.settings.jump_threshold));
+        if self.gesture.filtered_hand.replace(hand.right) != Some(hand.right) || jumped {
+            self.gesture
+                .fingertip_filters
+                .iter_mut()
+                .for_each(OneEuroFilter::reset);
+        }
+        std::array::from_fn(|i| {
+            self.gesture.fingertip_filters[i].filter(points[i], delta, &smoothing)
+        })
+    }
+
+    /// moves the solver model's fade towards `visible` by `delta` seconds worth of
+    /// [`SolverSettings::fade_duration`], hiding it once it faded out completely
+    fn fade_solver_model(&mut self, visible: bool, delta: f32) {
+        let step = if self.settings.fade_duration.is_zero() {
+            1.0
+        } else {
+            delta / self.settings.fade_duration.as_secs_f32()
+        };
+        let was_shown = self.solver_fade > 0.0;
+        self.solver_fade = if visible {
+            self.solver_fade + step
+        } else {
+            self.solver_fade - step
+        }
+        .clamp(0.0, 1.0);
+        let shown = self.solver_fade > 0.0;
+        if shown != was_shown {
+            _ = self.solver_model.set_enabled(shown);
+        }
+        if shown {
+            self.set_solver_emission(self.solver_fade_intensity * self.solver_fade);
+        }
+    }
+
+    /// only sends the emission color when it or the requested intensity changed
+    fn set_solver_emission(&mut self, intensity: f32) {
+        if self.solver_emission == intensity {
+            return;
+        }
+        let Some(mut color) = *self.solver_color.borrow_and_update() else {
+            return;
+        };
+        color.c.r *= intensity;
+        color.c.g *= intensity;
+        color.c.b *= intensity;
+        if self
+            .solver_part
+            .set_material_parameter("emission_factor", MaterialParameter::Color(color))
+            .is_ok()
+        {
+            self.solver_emission = intensity;
+        }
+    }
+}
+
+/// tints the solver target with the accent color, brightened to `emission_intensity`,
+/// for as long as the returned handle is kept
+fn follow_accent_color(
+    conn: Connection,
+    emission_intensity: f32,
+    solver_color_tx: watch::Sender<Option<Color>>,
+    solver_target_part: ModelPart,
+) -> AbortOnDrop {
+    let emission_intensity = emission_intensity.clamp(0.0, 4.0);
+    let mut accent_color = AccentColor::new(conn);
+    let task = tokio::task::spawn(async move {
+        while accent_color.color.changed().await.is_ok() {
+            let mut color = accent_color.color();
+
+            // bad hack so we can get a max value color
+            let greatest_channel = color.c.r.max(color.c.g).max(color.c.b);
+            if greatest_channel < 0.001 {
+                // a black accent color can't be brightened, glow white instead of NaN
+                color.c.r = emission_intensity;
+                color.c.g = emission_intensity;
+                color.c.b = emission_intensity;
+            } else {
+                let factor = emission_intensity / greatest_channel;
+                color.c.r *= factor;
+                color.c.g *= factor;
+                color.c.b *= factor;
+            }
+
+            _ = solver_color_tx.send(Some(color));
+            solver_target_part
+                .set_material_parameter("emission_factor", MaterialParameter::Color(color))
+                .unwrap();
+        }
+    });
+    AbortOnDrop(task.abort_handle())
+}
+
+/// a small grid around the point a released object would snap to
+fn grid_hint(position: Vec3, step: f32) -> Vec<Line> {
+    let center = snap_to_grid(position, step);
+    let line = |from: Vec3, to: Vec3| Line {
+        points: [from, to]
+            .into_iter()
+            .map(|p| LinePoint {
+                point: p.into(),
+                thickness: 0.001,
+                color: rgba_linear!(1.0, 1.0, 1.0, 0.25),
+            })
+            .collect(),
+        cyclic: false,
+    };
+    (-1..=1)
+        .flat_map(|i| {
+            let offset = i as f32 * step;
+            [
+                line(
+                    center + Vec3::new(offset, 0.0, -step),
+                    center + Vec3::new(offset, 0.0, step),
+                ),
+                line(
+                    center + Vec3::new(-step, 0.0, offset),
+                    center + Vec3::new(step, 0.0, offset),
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn pinching(hand: &Hand) -> bool {
+    Vec3::from(hand.thumb.tip.position).distance(hand.index.tip.position.into())
+        - (hand.thumb.tip.radius + hand.index.tip.radius)
+        < 0.005
+}
+
+/// the parts of a tracked hand the gestures look at, relative to the ring's input handler.
+///
+/// kept apart from [`Hand`] so recorded hands can go through the same gesture recognition,
+/// see [`crate::recording::replay_gestures`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GestureHand {
+    pub right: bool,
+    pub thumb_tip: Vec3,
+    pub thumb_radius: f32,
+    pub index_tip: Vec3,
+    pub index_radius: f32,
+    pub middle_tip: Vec3,
+    pub palm_position: Vec3,
+    pub palm_rotation: Quat,
+}
+impl From<&Hand> for GestureHand {
+    fn from(hand: &Hand) -> Self {
+        Self {
+            right: hand.right,
+            thumb_tip: hand.thumb.tip.position.into(),
+            thumb_radius: hand.thumb.tip.radius,
+            index_tip: hand.index.tip.position.into(),
+            index_radius: hand.index.tip.radius,
+            middle_tip: hand.middle.tip.position.into(),
+            palm_position: hand.palm.position.into(),
+            palm_rotation: hand.palm.rotation.into(),
+        }
+    }
+}
+impl GestureHand {
+    /// thumb, index and middle fingertip, wound so the triangle's normal points the same way
+    /// relative to the palm on either hand. left hands are mirrored right hands,
+    /// so their points are reversed to undo the flipped winding
+    pub fn triangle(&self) -> [Vec3; 3] {
+        let mut points = [self.thumb_tip, self.index_tip, self.middle_tip];
+        if !self.right {
+            points.reverse();
+        }
+        points
+    }
+    /// distance between thumb and index tip (m, between the joint surfaces)
+    pub fn spread(&self) -> f32 {
+        self.thumb_tip.distance(self.index_tip) - (self.thumb_radius + self.index_radius)
+    }
+    /// whether the palm normal is within `max_angle` of the direction from the palm to the
+    /// fingertip triangle, the direction the hand selects along
+    pub fn palm_faces_selection(&self, max_angle: f32) -> bool {
+        let [a, b, c] = self.triangle();
+        let Some(selection_dir) = ((a + b + c) / 3.0 - self.palm_position).try_normalize() else {
+            return true;
+        };
+        let normal = self.palm_rotation * Vec3::NEG_Y;
+        normal.angle_between(selection_dir) <= max_angle
+    }
+    /// whether the hand levitates, `acting` is whether it did last frame
+    pub fn levitating(
+        &self,
+        acting: bool,
+        spread: &SpreadThresholds,
+        palm_facing_angle: f32,
+    ) -> bool {
+        let threshold = if acting { spread.exit } else { spread.enter };
+        // only gate starting, turning the hand while levitating is fine
+        self.spread() > threshold && (acting || self.palm_faces_selection(palm_facing_angle))
+    }
+}
+
+/// the incenter of the triangle (the center of the largest circle that fits inside it) and a
+/// rotation whose -Z is the triangle's normal, turned as little as possible away from `ref_quat`.
+///
+/// the incenter weighs each corner by the length of the side across from it, so it always lies
+/// inside the triangle and is pulled less towards a single outstretched fingertip than the
+/// centroid. for an equilateral triangle it's the centroid, for a right triangle with legs `a`
+/// and `b` it's `(a + b - hypotenuse) / 2` away from both legs.
+/// the normal follows the right hand rule around `a`, `b`, `c`.
+///
+/// `None` when the points are (nearly) coincident or collinear,
+/// hand triangles should come from [`GestureHand::triangle`] to be wound consistently
+fn get_position_and_normal_from_triangle(
+    points: [Vec3; 3],
+    ref_quat: Quat,
+) -> Option<(Vec3, Quat)> {
+    const EPSILON: f32 = 1e-10;
+    let [a, b, c] = points;
+    let ab = a.distance(b);
+    let bc = b.distance(c);
+    let ca = c.distance(a);
+    let perimeter = ab + bc + ca;
+    if perimeter < EPSILON {
+        return None;
+    }
+    let point = ((bc * a) + (ca * b) + (ab * c)) / perimeter;
+    let ab = b - a;
+    let ac = c - a;
+    let cross = ab.cross(ac);
+    if cross.length_squared() < EPSILON {
+        return None;
+    }
+    let normal = cross.normalize();
+    Some((
+        point,
+        ref_quat * Quat::from_rotation_arc(Vec3::NEG_Z, ref_quat.inverse() * normal),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interface_enable_round_trip() {
+        let (enabled_tx, mut enabled_requests) = watch::channel(true);
+        let interface = SolverInterface {
+            enabled: enabled_tx,
+        };
+
+        interface.set_enabled(false);
+        assert!(enabled_requests.has_changed().unwrap());
+        assert!(!*enabled_requests.borrow_and_update());
+        assert!(!interface.enabled());
+
+        interface.set_enabled(true);
+        assert!(enabled_requests.has_changed().unwrap());
+        assert!(*enabled_requests.borrow_and_update());
+        assert!(interface.enabled());
+    }
+
+    #[test]
+    fn disabling_forgets_gestures_in_progress() {
+        let smoothing = OneEuroSettings::default();
+        let mut gesture = GestureState {
+            input_source: InputSource::Pointer,
+            ring_input_since: Some(Instant::now()),
+            fingertip_filters: Default::default(),
+            filtered_hand: Some(true),
+            bimanual_separation: Some(0.2),
+            capture_cooldown: 0.5,
+        };
+        for filter in &mut gesture.fingertip_filters {
+            filter.filter(Vec3::ZERO, 0.011, &smoothing);
+        }
+        gesture.reset();
+        assert_eq!(gesture.input_source, InputSource::Ring);
+        assert!(gesture.ring_input_since.is_none());
+        assert!(gesture.filtered_hand.is_none());
+        assert!(gesture.bimanual_separation.is_none());
+        assert!(gesture.capture_cooldown <= 0.0);
+        // the fingertips aren't smoothed towards where they were before disabling
+        for filter in &mut gesture.fingertip_filters {
+            assert_eq!(filter.filter(Vec3::ONE, 0.011, &smoothing), Vec3::ONE);
+        }
+    }
+
+    /// a hand held out flat with the palm facing down, `right` mirrors it along X
+    fn flat_hand(right: bool) -> GestureHand {
+        let side = if right { 1.0 } else { -1.0 };
+        let mirror = |point: Vec3| Vec3::new(point.x * side, point.y, point.z);
+        GestureHand {
+            right,
+            thumb_tip: mirror(Vec3::new(-0.04, -0.02, -0.05)),
+            thumb_radius: 0.008,
+            index_tip: mirror(Vec3::new(-0.01, -0.02, -0.09)),
+            index_radius: 0.008,
+            middle_tip: mirror(Vec3::new(0.01, -0.02, -0.095)),
+            palm_position: Vec3::ZERO,
+            palm_rotation: Quat::IDENTITY,
+        }
+    }
+
+    #[test]
+    fn mirrored_hands_share_the_normal() {
+        let palm_normal = Vec3::NEG_Y;
+        let normals = [true, false].map(|right| {
+            let (_, rotation) =
+                get_position_and_normal_from_triangle(flat_hand(right).triangle(), Quat::IDENTITY)
+                    .unwrap();
+            rotation * Vec3::NEG_Z
+        });
+        for normal in normals {
+            assert!(normal.dot(palm_normal) > 0.9, "{normal:?}");
+        }
+        assert!(normals[0].abs_diff_eq(normals[1], 1e-5));
+    }
+
+    #[test]
+    fn palm_up_does_not_face_the_selection() {
+        let palm_down = flat_hand(true);
+        let palm_up = GestureHand {
+            palm_rotation: Quat::from_rotation_z(std::f32::consts::PI),
+            ..palm_down
+        };
+        assert!(palm_down.palm_faces_selection(FRAC_PI_2));
+        assert!(!palm_up.palm_faces_selection(FRAC_PI_2));
+        // the default is lenient enough for a hand turned on its side
+        let default_angle = SolverSettings::default().palm_facing_angle;
+        assert!(palm_down.palm_faces_selection(default_angle));
+        assert!(palm_up.palm_faces_selection(default_angle));
+        // only starting is gated, a hand already levitating keeps going when turned over
+        let spread = SpreadThresholds::default();
+        let spread_up = GestureHand {
+            index_tip: palm_up.thumb_tip + Vec3::X * 0.06,
+            ..palm_up
+        };
+        assert!(!spread_up.levitating(false, &spread, FRAC_PI_2));
+        assert!(spread_up.levitating(true, &spread, FRAC_PI_2));
+    }
+
+    #[test]
+    fn equilateral_incenter_is_the_centroid() {
+        let points = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.0, 0.0),
+            Vec3::new(0.05, 0.1 * 3f32.sqrt() / 2.0, 0.0),
+        ];
+        let (center, rotation) =
+            get_position_and_normal_from_triangle(points, Quat::IDENTITY).unwrap();
+        let centroid = (points[0] + points[1] + points[2]) / 3.0;
+        assert!(center.abs_diff_eq(centroid, 1e-6), "{center:?}");
+        // counter clockwise seen from +Z
+        assert!((rotation * Vec3::NEG_Z).abs_diff_eq(Vec3::Z, 1e-5));
+    }
+
+    #[test]
+    fn right_triangle_incenter() {
+        let (a, b) = (0.03, 0.04);
+        let points = [Vec3::ZERO, Vec3::new(a, 0.0, 0.0), Vec3::new(0.0, 0.0, -b)];
+        let (center, rotation) =
+            get_position_and_normal_from_triangle(points, Quat::IDENTITY).unwrap();
+        let radius = (a + b - a.hypot(b)) / 2.0;
+        assert!(
+            center.abs_diff_eq(Vec3::new(radius, 0.0, -radius), 1e-6),
+            "{center:?}"
+        );
+        let normal = rotation * Vec3::NEG_Z;
+        for edge in [points[1] - points[0], points[2] - points[0]] {
+            assert!(normal.dot(edge).abs() < 1e-6);
+        }
+        assert!(normal.abs_diff_eq(Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn degenerate_triangles_have_no_pose() {
+        let point = Vec3::new(0.1, -0.2, 0.3);
+        assert_eq!(
+            get_position_and_normal_from_triangle([point; 3], Quat::IDENTITY),
+            None
+        );
+        let collinear = [
+            Vec3::ZERO,
+            Vec3::new(0.01, 0.02, 0.03),
+            Vec3::new(0.03, 0.06, 0.09),
+        ];
+        assert_eq!(
+            get_position_and_normal_from_triangle(collinear, Quat::IDENTITY),
+            None
+        );
+    }
+}